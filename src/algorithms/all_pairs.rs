@@ -0,0 +1,120 @@
+use firestorm::{profile_fn, profile_section};
+use crate::algorithms::shortest_path::{bellman_ford, dijkstra_with, reconstruct_path, relax_from, Cost, NegativeCycle, Path};
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeStore, VHandleOf};
+
+/// Shortest path costs between every pair of vertices, stored row major. Row `from` of `predecessors` is the
+/// shortest path tree rooted at `from`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceMatrix<H> {
+    pub vertices_count: usize,
+    pub distances: Vec<Option<Cost>>,
+    pub predecessors: Vec<Option<H>>,
+}
+
+impl<H: HandleIndex> DistanceMatrix<H> {
+    fn new(vertices_count: usize) -> Self {
+        return DistanceMatrix{
+            vertices_count,
+            distances: vec![None; vertices_count * vertices_count],
+            predecessors: vec![None; vertices_count * vertices_count],
+        }
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn cell(&self, from: H, to: H) -> usize {
+        return from.index() * self.vertices_count + to.index();
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn distance(&self, from: H, to: H) -> Option<Cost> {
+        return self.distances[self.cell(from, to)];
+    }
+
+    pub fn row(&self, from: H) -> &[Option<Cost>] {
+        let start = from.index() * self.vertices_count;
+        return &self.distances[start..start + self.vertices_count];
+    }
+
+    /// Shortest path from `from` to `to`, `None` if `to` is unreachable.
+    pub fn path(&self, from: H, to: H) -> Option<Path<H>> {
+        let cost = self.distance(from, to)?;
+        let start = from.index() * self.vertices_count;
+        return Some(Path{
+            vertices: reconstruct_path(&self.predecessors[start..start + self.vertices_count], to),
+            cost,
+        });
+    }
+}
+
+/// All pairs shortest paths in O(V^3) time and O(V^2) memory, meant for small dense graphs.
+/// Negative weights are allowed, fails with a negative cycle if there is one.
+pub fn floyd_warshall<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Result<DistanceMatrix<VHandleOf<Edges>>, NegativeCycle<VHandleOf<Edges>>> {
+    profile_fn!(floyd_warshall);
+    let n = vertices_count;
+    let mut matrix = DistanceMatrix::new(n);
+    for from in 0..n {
+        let vertex = VHandleOf::<Edges>::from_index(from);
+        matrix.distances[from * n + from] = Some(0);
+        for edge in edge_storage.edges(vertex) {
+            let cell = from * n + edge.vh().index();
+            let weight = edge.wgt().into();
+            if matrix.distances[cell].is_none_or(|current| weight < current) {
+                matrix.distances[cell] = Some(weight);
+                matrix.predecessors[cell] = Some(vertex);
+            }
+        }
+    }
+
+    for k in 0..n {
+        profile_section!(floyd_warshall_pivot);
+        for i in 0..n {
+            let Some(through_k) = matrix.distances[i * n + k] else {
+                continue;
+            };
+            for j in 0..n {
+                let Some(from_k) = matrix.distances[k * n + j] else {
+                    continue;
+                };
+                let distance = through_k + from_k;
+                if matrix.distances[i * n + j].is_none_or(|current| distance < current) {
+                    matrix.distances[i * n + j] = Some(distance);
+                    matrix.predecessors[i * n + j] = matrix.predecessors[k * n + j];
+                }
+            }
+        }
+
+        // Once a cycle goes negative the distances shrink exponentially with every further pivot and would
+        // overflow, so stop right away. Without one they stay bounded by simple paths.
+        if let Some(vertex) = (0..n).find(|vertex| matrix.distances[vertex * n + vertex].is_some_and(|distance| distance < 0)) {
+            // The vertex lies on a negative cycle, let the single source search extract it
+            let vertex = VHandleOf::<Edges>::from_index(vertex);
+            return Err(bellman_ford(edge_storage, vertex, n).expect_err("A negative cycle is reachable from its own vertex"));
+        }
+    }
+    return Ok(matrix);
+}
+
+/// All pairs shortest paths for sparse graphs with negative weights. The weights are made non negative with
+/// potentials from a Bellman-Ford pass and every vertex is then searched with Dijkstra, O(V E log V) in total.
+pub fn johnson<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Result<DistanceMatrix<VHandleOf<Edges>>, NegativeCycle<VHandleOf<Edges>>> {
+    profile_fn!(johnson);
+    let n = vertices_count;
+    // A virtual source with a 0 weight edge to every vertex, all vertices start at distance 0
+    let sources: Vec<VHandleOf<Edges>> = (0..n).map(VHandleOf::<Edges>::from_index).collect();
+    let mut potentials = vec![Some(0); n];
+    let mut predecessors = vec![None; n];
+    relax_from(edge_storage, &mut potentials, &mut predecessors, &sources)?;
+    let potentials: Vec<Cost> = potentials.into_iter().map(|potential| potential.unwrap()).collect();
+
+    let mut matrix = DistanceMatrix::new(n);
+    for &source in &sources {
+        let paths = dijkstra_with(edge_storage, source, n, None, |src, target, weight| weight + potentials[src.index()] - potentials[target.index()]);
+        let start = source.index() * n;
+        for target in 0..n {
+            matrix.distances[start + target] = paths.distances[target].map(|distance| distance - potentials[source.index()] + potentials[target]);
+        }
+        matrix.predecessors[start..start + n].copy_from_slice(&paths.predecessors);
+    }
+    return Ok(matrix);
+}
@@ -0,0 +1,212 @@
+use firestorm::profile_fn;
+use crate::algorithms::union_find::UnionFind;
+use crate::edge_storage::EdgeStorage;
+use crate::graph::Graph;
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeOf, EdgeOperator, EdgeStore, VHandleOf};
+
+/// A component id for every vertex handle. Ids are dense, `0..count`. Vertices left out by the liveness check
+/// of the `_with` variants belong to no component and are labeled `usize::MAX`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    pub labels: Vec<usize>,
+    pub count: usize,
+}
+
+impl Components {
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn component<H: HandleIndex>(&self, vertex: H) -> usize {
+        return self.labels[vertex.index()];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn same_component<H: HandleIndex>(&self, a: H, b: H) -> bool {
+        return self.labels[a.index()] != UNVISITED && self.labels[a.index()] == self.labels[b.index()];
+    }
+
+    /// The vertices of every component, each list in handle order.
+    pub fn groups<H: HandleIndex>(&self) -> Vec<Vec<H>> {
+        let mut groups = vec![Vec::new(); self.count];
+        for (vertex, label) in self.labels.iter().enumerate().filter(|(_, label)| **label != UNVISITED) {
+            groups[*label].push(H::from_index(vertex));
+        }
+        return groups;
+    }
+}
+
+const UNVISITED: usize = usize::MAX;
+
+/// Strongly connected components with Tarjan's algorithm, using an explicit stack instead of recursion.
+/// Components are numbered in topological order of the condensation, edges only go from lower to higher ids.
+/// Slots of removed vertices count as components of their own, see `strongly_connected_components_with`.
+pub fn strongly_connected_components<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Components {
+    return strongly_connected_components_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Same as `strongly_connected_components`, but only vertices accepted by `is_alive` are taken into account.
+/// Pass `|vertex| graph.is_alive(vertex)` for a graph that had vertices removed.
+pub fn strongly_connected_components_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> Components
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(strongly_connected_components);
+    let mut order = vec![UNVISITED; vertices_count];
+    let mut low_links = vec![0usize; vertices_count];
+    let mut on_stack = vec![false; vertices_count];
+    let mut component_stack = Vec::new();
+    let mut call_stack: Vec<(VHandleOf<Edges>, usize)> = Vec::new();
+    let mut labels = vec![UNVISITED; vertices_count];
+    let mut next_order = 0;
+    let mut count = 0;
+
+    for root in 0..vertices_count {
+        if order[root] != UNVISITED || !is_alive(VHandleOf::<Edges>::from_index(root)) {
+            continue;
+        }
+        order[root] = next_order;
+        low_links[root] = next_order;
+        next_order += 1;
+        on_stack[root] = true;
+        component_stack.push(root);
+        call_stack.push((VHandleOf::<Edges>::from_index(root), 0));
+
+        while let Some((vertex, offset)) = call_stack.last_mut() {
+            let vertex = vertex.index();
+            let edges = edge_storage.edges(VHandleOf::<Edges>::from_index(vertex));
+            if *offset < edges.len() {
+                let next = edges[*offset].vh();
+                *offset += 1;
+                let next_index = next.index();
+                if !is_alive(next) {
+                    continue;
+                }
+                if order[next_index] == UNVISITED {
+                    order[next_index] = next_order;
+                    low_links[next_index] = next_order;
+                    next_order += 1;
+                    on_stack[next_index] = true;
+                    component_stack.push(next_index);
+                    call_stack.push((next, 0));
+                } else if on_stack[next_index] {
+                    low_links[vertex] = low_links[vertex].min(order[next_index]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some((parent, _)) = call_stack.last() {
+                low_links[parent.index()] = low_links[parent.index()].min(low_links[vertex]);
+            }
+            if low_links[vertex] == order[vertex] {
+                loop {
+                    let member = component_stack.pop().unwrap();
+                    on_stack[member] = false;
+                    labels[member] = count;
+                    if member == vertex {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+
+    // Tarjan finishes sink components first, flip the ids to get a topological order
+    for label in labels.iter_mut().filter(|label| **label != UNVISITED) {
+        *label = count - 1 - *label;
+    }
+    return Components{labels, count};
+}
+
+/// Collapses every strongly connected component into one vertex holding its members. The result is acyclic,
+/// vertex `i` is component `i` and there is one edge per pair of linked components carrying the smallest weight
+/// among the links.
+/// Slots of removed vertices become components of their own, see `condense_with`.
+pub fn condense<Edges>(edge_storage: &Edges, vertices_count: usize) -> (Graph<Vec<VHandleOf<Edges>>, EdgeStorage<EdgeOf<Edges>>>, Components)
+where Edges: EdgeStore {
+    return condense_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Same as `condense`, but built on `strongly_connected_components_with`, so vertices rejected by `is_alive`
+/// end up in no component and the condensed graph has no vertex for them.
+pub fn condense_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> (Graph<Vec<VHandleOf<Edges>>, EdgeStorage<EdgeOf<Edges>>>, Components)
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(condense);
+    let components = strongly_connected_components_with(edge_storage, vertices_count, is_alive);
+    let groups = components.groups::<VHandleOf<Edges>>();
+    let mut condensed = Graph::with_storage(EdgeStorage::<EdgeOf<Edges>>::new());
+
+    // Position of the link to a component in `targets`, only valid while `seen_from[target] == component`
+    let mut positions = vec![0usize; components.count];
+    let mut seen_from = vec![UNVISITED; components.count];
+    let mut component_edges = Vec::with_capacity(components.count);
+
+    for (component, members) in groups.iter().enumerate() {
+        let mut targets: Vec<EdgeOf<Edges>> = Vec::new();
+        for member in members {
+            for edge in edge_storage.edges(*member) {
+                let target = components.component(edge.vh());
+                if target == component || target == UNVISITED {
+                    continue;
+                }
+                if seen_from[target] != component {
+                    seen_from[target] = component;
+                    positions[target] = targets.len();
+                    targets.push(edge.set_vid(VHandleOf::<Edges>::from_index(target)));
+                    continue;
+                }
+                let link = &mut targets[positions[target]];
+                if edge.wgt().into() < link.wgt().into() {
+                    *link = link.set_wgt(edge.wgt());
+                }
+            }
+        }
+        component_edges.push(targets);
+    }
+
+    for (members, targets) in groups.into_iter().zip(&component_edges) {
+        condensed.create(members, targets.len());
+    }
+    for (component, targets) in component_edges.iter().enumerate() {
+        condensed.edges.add_edges(VHandleOf::<Edges>::from_index(component), targets);
+    }
+    return (condensed, components);
+}
+
+/// Components when edge directions are ignored. For an `UndirectedGraph` these are its connected components.
+/// Ids are assigned in order of the smallest handle of each component.
+/// Slots of removed vertices count as components of their own, see `weakly_connected_components_with`.
+pub fn weakly_connected_components<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Components {
+    return weakly_connected_components_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Same as `weakly_connected_components`, but only vertices accepted by `is_alive` are taken into account.
+pub fn weakly_connected_components_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> Components
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(weakly_connected_components);
+    let mut sets = UnionFind::<VHandleOf<Edges>>::new(vertices_count);
+    for vertex in 0..vertices_count {
+        let vertex = VHandleOf::<Edges>::from_index(vertex);
+        if !is_alive(vertex) {
+            continue;
+        }
+        for edge in edge_storage.edges(vertex).iter().filter(|edge| is_alive(edge.vh())) {
+            sets.union(vertex, edge.vh());
+        }
+    }
+
+    let mut root_labels = vec![UNVISITED; vertices_count];
+    let mut labels = Vec::with_capacity(vertices_count);
+    let mut count = 0;
+    for vertex in 0..vertices_count {
+        if !is_alive(VHandleOf::<Edges>::from_index(vertex)) {
+            labels.push(UNVISITED);
+            continue;
+        }
+        let root = sets.find(VHandleOf::<Edges>::from_index(vertex)).index();
+        if root_labels[root] == UNVISITED {
+            root_labels[root] = count;
+            count += 1;
+        }
+        labels.push(root_labels[root]);
+    }
+    return Components{labels, count};
+}
@@ -0,0 +1,320 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+use firestorm::{profile_fn, profile_method};
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeStore, VHandleOf};
+
+/// Flow amounts and capacities, summed in `i64` regardless of the weight width of the storage.
+pub type Flow = i64;
+
+/// Maximum flow between two vertices together with a minimum cut.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxFlow<H> {
+    pub value: Flow,
+    /// Flow over every edge, `flows[edge_offsets[v] + k]` belongs to the `k`-th edge of `v`.
+    pub flows: Vec<Flow>,
+    pub edge_offsets: Vec<usize>,
+    /// Vertices reachable from the source in the final residual graph, the source side of the minimum cut.
+    pub source_side: Vec<bool>,
+    /// Edges from the source side to the sink side. Their capacities sum up to `value`.
+    pub cut: Vec<(H, H)>,
+}
+
+impl<H: HandleIndex> MaxFlow<H> {
+    /// Flows aligned with `edges(vertex)`.
+    pub fn edge_flows(&self, vertex: H) -> &[Flow] {
+        return &self.flows[self.edge_offsets[vertex.index()]..self.edge_offsets[vertex.index() + 1]];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn edge_flow(&self, vertex: H, offset: usize) -> Flow {
+        return self.flows[self.edge_offsets[vertex.index()] + offset];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn is_source_side(&self, vertex: H) -> bool {
+        return self.source_side[vertex.index()];
+    }
+}
+
+/// Residual network in CSR layout. Every edge of the storage becomes a forward arc with the edge weight as
+/// capacity (non positive weights give no capacity) and a reverse arc without capacity.
+pub(in crate) struct Residual {
+    pub(in crate) first: Vec<usize>,
+    pub(in crate) heads: Vec<usize>,
+    pub(in crate) capacities: Vec<Flow>,
+    pub(in crate) reverse: Vec<usize>,
+    /// Forward arc of every storage edge, in storage order.
+    pub(in crate) edge_arcs: Vec<usize>,
+    pub(in crate) edge_offsets: Vec<usize>,
+}
+
+impl Residual {
+    pub(in crate) fn new<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Self {
+        profile_method!(new);
+        let mut degrees = vec![0usize; vertices_count + 1];
+        let mut edge_offsets = Vec::with_capacity(vertices_count + 1);
+        edge_offsets.push(0);
+        for vertex in 0..vertices_count {
+            let edges = edge_storage.edges(VHandleOf::<Edges>::from_index(vertex));
+            degrees[vertex] += edges.len();
+            for edge in edges {
+                degrees[edge.vh().index()] += 1;
+            }
+            edge_offsets.push(edge_offsets[vertex] + edges.len());
+        }
+
+        let mut first = Vec::with_capacity(vertices_count + 1);
+        first.push(0);
+        for vertex in 0..vertices_count {
+            first.push(first[vertex] + degrees[vertex]);
+        }
+        let arcs_count = first[vertices_count];
+        let mut fill = first.clone();
+        let mut residual = Residual{
+            heads: vec![0; arcs_count],
+            capacities: vec![0; arcs_count],
+            reverse: vec![0; arcs_count],
+            edge_arcs: Vec::with_capacity(edge_offsets[vertices_count]),
+            first,
+            edge_offsets,
+        };
+
+        for vertex in 0..vertices_count {
+            for edge in edge_storage.edges(VHandleOf::<Edges>::from_index(vertex)) {
+                let target = edge.vh().index();
+                let forward = fill[vertex];
+                fill[vertex] += 1;
+                let backward = fill[target];
+                fill[target] += 1;
+                residual.heads[forward] = target;
+                residual.heads[backward] = vertex;
+                residual.capacities[forward] = edge.wgt().into().max(0);
+                residual.reverse[forward] = backward;
+                residual.reverse[backward] = forward;
+                residual.edge_arcs.push(forward);
+            }
+        }
+        return residual;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub(in crate) fn vertices_count(&self) -> usize {
+        return self.first.len() - 1;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub(in crate) fn arcs(&self, vertex: usize) -> Range<usize> {
+        return self.first[vertex]..self.first[vertex + 1];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub(in crate) fn push(&mut self, arc: usize, amount: Flow) {
+        self.capacities[arc] -= amount;
+        self.capacities[self.reverse[arc]] += amount;
+    }
+
+    /// Flow over the forward arc, the capacity its reverse arc gained.
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub(in crate) fn flow(&self, arc: usize) -> Flow {
+        return self.capacities[self.reverse[arc]];
+    }
+
+    /// Vertices reachable from `source` over arcs with capacity left.
+    pub(in crate) fn reachable(&self, source: usize) -> Vec<bool> {
+        let mut reached = vec![false; self.vertices_count()];
+        let mut to_visit = vec![source];
+        reached[source] = true;
+        while let Some(vertex) = to_visit.pop() {
+            for arc in self.arcs(vertex) {
+                let head = self.heads[arc];
+                if self.capacities[arc] > 0 && !reached[head] {
+                    reached[head] = true;
+                    to_visit.push(head);
+                }
+            }
+        }
+        return reached;
+    }
+
+    pub(in crate) fn into_max_flow<H: HandleIndex>(self, source: usize, value: Flow) -> MaxFlow<H> {
+        let source_side = self.reachable(source);
+        let mut cut = Vec::new();
+        for vertex in 0..self.vertices_count() {
+            if !source_side[vertex] {
+                continue;
+            }
+            for &arc in &self.edge_arcs[self.edge_offsets[vertex]..self.edge_offsets[vertex + 1]] {
+                let head = self.heads[arc];
+                if !source_side[head] && self.flow(arc) > 0 {
+                    cut.push((H::from_index(vertex), H::from_index(head)));
+                }
+            }
+        }
+        return MaxFlow{
+            value,
+            flows: self.edge_arcs.iter().map(|arc| self.flow(*arc)).collect(),
+            edge_offsets: self.edge_offsets,
+            source_side,
+            cut,
+        };
+    }
+}
+
+/// Maximum flow from `source` to `sink` with Dinic's algorithm, treating edge weights as capacities.
+/// O(V^2 E) in general and much faster on unit capacity networks.
+pub fn dinic<Edges: EdgeStore>(edge_storage: &Edges, source: VHandleOf<Edges>, sink: VHandleOf<Edges>, vertices_count: usize) -> MaxFlow<VHandleOf<Edges>> {
+    profile_fn!(dinic);
+    let mut residual = Residual::new(edge_storage, vertices_count);
+    let (source, sink) = (source.index(), sink.index());
+    let mut value = 0;
+    if source == sink {
+        return residual.into_max_flow(source, value);
+    }
+
+    let mut levels = vec![usize::MAX; vertices_count];
+    let mut next_arcs = vec![0usize; vertices_count];
+    let mut path: Vec<usize> = Vec::new();
+    let mut queue = VecDeque::new();
+    loop {
+        // Level graph over arcs with capacity left
+        levels.fill(usize::MAX);
+        levels[source] = 0;
+        queue.push_back(source);
+        while let Some(vertex) = queue.pop_front() {
+            for arc in residual.arcs(vertex) {
+                let head = residual.heads[arc];
+                if residual.capacities[arc] > 0 && levels[head] == usize::MAX {
+                    levels[head] = levels[vertex] + 1;
+                    queue.push_back(head);
+                }
+            }
+        }
+        if levels[sink] == usize::MAX {
+            break;
+        }
+
+        // Blocking flow, advancing along level increasing arcs and retreating from dead ends
+        next_arcs.copy_from_slice(&residual.first[..vertices_count]);
+        path.clear();
+        let mut vertex = source;
+        loop {
+            if vertex == sink {
+                let bottleneck = path.iter().map(|arc| residual.capacities[*arc]).min().unwrap();
+                for arc in &path {
+                    residual.push(*arc, bottleneck);
+                }
+                value += bottleneck;
+                let saturated = path.iter().position(|arc| residual.capacities[*arc] == 0).unwrap();
+                path.truncate(saturated);
+                vertex = path.last().map_or(source, |arc| residual.heads[*arc]);
+                continue;
+            }
+
+            let end = residual.first[vertex + 1];
+            while next_arcs[vertex] != end {
+                let arc = next_arcs[vertex];
+                let head = residual.heads[arc];
+                if residual.capacities[arc] > 0 && levels[head] == levels[vertex] + 1 {
+                    break;
+                }
+                next_arcs[vertex] += 1;
+            }
+            if next_arcs[vertex] != end {
+                let arc = next_arcs[vertex];
+                path.push(arc);
+                vertex = residual.heads[arc];
+                continue;
+            }
+
+            if vertex == source {
+                break;
+            }
+            levels[vertex] = usize::MAX; // Dead end, nothing leads to the sink from here in this phase
+            let arc = path.pop().unwrap();
+            vertex = residual.heads[residual.reverse[arc]];
+            next_arcs[vertex] += 1;
+        }
+    }
+    return residual.into_max_flow(source, value);
+}
+
+/// Maximum flow from `source` to `sink` with FIFO push-relabel and the gap heuristic, O(V^3).
+/// Usually the better choice for dense networks.
+pub fn push_relabel<Edges: EdgeStore>(edge_storage: &Edges, source: VHandleOf<Edges>, sink: VHandleOf<Edges>, vertices_count: usize) -> MaxFlow<VHandleOf<Edges>> {
+    profile_fn!(push_relabel);
+    let mut residual = Residual::new(edge_storage, vertices_count);
+    let (source, sink) = (source.index(), sink.index());
+    let n = vertices_count;
+    if source == sink {
+        return residual.into_max_flow(source, 0);
+    }
+
+    let mut heights = vec![0usize; n];
+    let mut height_counts = vec![0usize; 2 * n + 1];
+    let mut excess = vec![0 as Flow; n];
+    let mut next_arcs = residual.first[..n].to_vec();
+    let mut active = VecDeque::new();
+    heights[source] = n;
+    height_counts[0] = n - 1;
+    height_counts[n] = 1;
+
+    for arc in residual.arcs(source) {
+        let amount = residual.capacities[arc];
+        if amount == 0 {
+            continue;
+        }
+        let head = residual.heads[arc];
+        residual.push(arc, amount);
+        if excess[head] == 0 && head != sink && head != source {
+            active.push_back(head);
+        }
+        excess[head] += amount;
+    }
+
+    while let Some(vertex) = active.pop_front() {
+        // Discharge
+        while excess[vertex] > 0 {
+            if next_arcs[vertex] == residual.first[vertex + 1] {
+                let old_height = heights[vertex];
+                let new_height = residual.arcs(vertex)
+                    .filter(|arc| residual.capacities[*arc] > 0)
+                    .map(|arc| heights[residual.heads[arc]] + 1)
+                    .min()
+                    .expect("A vertex with excess has a residual arc back towards the source");
+                height_counts[old_height] -= 1;
+                heights[vertex] = new_height;
+                height_counts[new_height] += 1;
+                next_arcs[vertex] = residual.first[vertex];
+
+                if height_counts[old_height] == 0 && old_height < n {
+                    // Gap: nothing above the old height can reach the sink anymore
+                    for other in 0..n {
+                        if other != source && heights[other] > old_height && heights[other] < n + 1 {
+                            height_counts[heights[other]] -= 1;
+                            heights[other] = n + 1;
+                            height_counts[n + 1] += 1;
+                            next_arcs[other] = residual.first[other];
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let arc = next_arcs[vertex];
+            let head = residual.heads[arc];
+            if residual.capacities[arc] > 0 && heights[vertex] == heights[head] + 1 {
+                let amount = excess[vertex].min(residual.capacities[arc]);
+                residual.push(arc, amount);
+                excess[vertex] -= amount;
+                if excess[head] == 0 && head != sink && head != source {
+                    active.push_back(head);
+                }
+                excess[head] += amount;
+            } else {
+                next_arcs[vertex] += 1;
+            }
+        }
+    }
+    return residual.into_max_flow(source, excess[sink]);
+}
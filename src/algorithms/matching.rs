@@ -0,0 +1,989 @@
+use std::collections::VecDeque;
+use firestorm::profile_fn;
+use crate::algorithms::shortest_path::Cost;
+use crate::algorithms::topological::Cycle;
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeStore, VHandleOf};
+
+const UNMATCHED: usize = usize::MAX;
+
+/// A set of edges without common vertices. `mates[v]` is the vertex `v` is paired with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matching<H> {
+    pub mates: Vec<Option<H>>,
+    pub size: usize,
+}
+
+impl<H: HandleIndex> Matching<H> {
+    fn from_mates(mates: &[usize]) -> Self {
+        let size = mates.iter().filter(|mate| **mate != UNMATCHED).count() / 2;
+        return Matching{
+            mates: mates.iter().map(|mate| if *mate == UNMATCHED { None } else { Some(H::from_index(*mate)) }).collect(),
+            size,
+        };
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn mate(&self, vertex: H) -> Option<H> {
+        return self.mates[vertex.index()];
+    }
+
+    /// Every matched pair once, the smaller handle first.
+    pub fn pairs(&self) -> Vec<(H, H)> {
+        return self.mates.iter().enumerate()
+            .filter_map(|(vertex, mate)| mate.filter(|mate| vertex < mate.index()).map(|mate| (H::from_index(vertex), mate)))
+            .collect();
+    }
+}
+
+/// Edge lists with directions dropped, `neighbors[first[v]..first[v + 1]]` are the vertices sharing an edge with `v`.
+/// Symmetric storages such as the one of `UndirectedGraph` list every neighbour twice, which does no harm.
+pub(in crate) struct Adjacency {
+    pub(in crate) first: Vec<usize>,
+    pub(in crate) neighbors: Vec<usize>,
+}
+
+impl Adjacency {
+    pub(in crate) fn undirected<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Self {
+        let mut degrees = vec![0usize; vertices_count];
+        for vertex in 0..vertices_count {
+            for edge in edge_storage.edges(VHandleOf::<Edges>::from_index(vertex)) {
+                degrees[vertex] += 1;
+                degrees[edge.vh().index()] += 1;
+            }
+        }
+        let mut first = Vec::with_capacity(vertices_count + 1);
+        first.push(0);
+        for vertex in 0..vertices_count {
+            first.push(first[vertex] + degrees[vertex]);
+        }
+        let mut fill = first.clone();
+        let mut neighbors = vec![0usize; first[vertices_count]];
+        for vertex in 0..vertices_count {
+            for edge in edge_storage.edges(VHandleOf::<Edges>::from_index(vertex)) {
+                let target = edge.vh().index();
+                neighbors[fill[vertex]] = target;
+                fill[vertex] += 1;
+                neighbors[fill[target]] = vertex;
+                fill[target] += 1;
+            }
+        }
+        return Adjacency{first, neighbors};
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub(in crate) fn of(&self, vertex: usize) -> &[usize] {
+        return &self.neighbors[self.first[vertex]..self.first[vertex + 1]];
+    }
+}
+
+/// Two colouring of a bipartite graph, `right[v]` tells the side of `v`. The smallest vertex of every
+/// connected component is on the left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bipartition {
+    pub right: Vec<bool>,
+}
+
+impl Bipartition {
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn is_right<H: HandleIndex>(&self, vertex: H) -> bool {
+        return self.right[vertex.index()];
+    }
+
+    pub fn left<H: HandleIndex>(&self) -> Vec<H> {
+        return (0..self.right.len()).filter(|vertex| !self.right[*vertex]).map(H::from_index).collect();
+    }
+
+    pub fn right<H: HandleIndex>(&self) -> Vec<H> {
+        return (0..self.right.len()).filter(|vertex| self.right[*vertex]).map(H::from_index).collect();
+    }
+}
+
+/// Splits the vertices into two sides with every edge going across, ignoring edge directions.
+/// Fails with a cycle of odd length, which proves that no such split exists.
+pub fn bipartition<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Result<Bipartition, Cycle<VHandleOf<Edges>>> {
+    profile_fn!(bipartition);
+    let adjacency = Adjacency::undirected(edge_storage, vertices_count);
+    return two_color(&adjacency).map_err(|cycle| Cycle{vertices: cycle.into_iter().map(VHandleOf::<Edges>::from_index).collect()});
+}
+
+fn two_color(adjacency: &Adjacency) -> Result<Bipartition, Vec<usize>> {
+    let vertices_count = adjacency.first.len() - 1;
+    let mut colored = vec![false; vertices_count];
+    let mut right = vec![false; vertices_count];
+    let mut parents = vec![UNMATCHED; vertices_count];
+    let mut queue = VecDeque::new();
+
+    for root in 0..vertices_count {
+        if colored[root] {
+            continue;
+        }
+        colored[root] = true;
+        queue.push_back(root);
+        while let Some(vertex) = queue.pop_front() {
+            for &neighbor in adjacency.of(vertex) {
+                if !colored[neighbor] {
+                    colored[neighbor] = true;
+                    right[neighbor] = !right[vertex];
+                    parents[neighbor] = vertex;
+                    queue.push_back(neighbor);
+                } else if right[neighbor] == right[vertex] {
+                    return Err(odd_cycle(&parents, vertex, neighbor));
+                }
+            }
+        }
+    }
+    return Ok(Bipartition{right});
+}
+
+/// Joins the BFS tree paths of the two ends of an edge inside one colour class into a cycle.
+fn odd_cycle(parents: &[usize], a: usize, b: usize) -> Vec<usize> {
+    let path_to_root = |mut vertex: usize| {
+        let mut path = vec![vertex];
+        while parents[vertex] != UNMATCHED {
+            vertex = parents[vertex];
+            path.push(vertex);
+        }
+        return path;
+    };
+    let mut from_a = path_to_root(a);
+    let mut from_b = path_to_root(b);
+    // Drop the shared part above the lowest common ancestor
+    while from_a.len() > 1 && from_b.len() > 1 && from_a[from_a.len() - 2] == from_b[from_b.len() - 2] {
+        from_a.pop();
+        from_b.pop();
+    }
+    // a up to the ancestor, then down to b, the edge b - a closes the cycle
+    from_b.pop();
+    let mut cycle = from_a;
+    cycle.extend(from_b.into_iter().rev());
+    return cycle;
+}
+
+/// Maximum matching of a bipartite graph together with a minimum vertex cover of the same size (König).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BipartiteMatching<H> {
+    pub matching: Matching<H>,
+    pub partition: Bipartition,
+    pub vertex_cover: Vec<H>,
+}
+
+/// Maximum cardinality matching with Hopcroft-Karp in O(E sqrt(V)), ignoring edge directions.
+/// Fails with an odd cycle if the graph is not bipartite.
+pub fn hopcroft_karp<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Result<BipartiteMatching<VHandleOf<Edges>>, Cycle<VHandleOf<Edges>>> {
+    profile_fn!(hopcroft_karp);
+    let adjacency = Adjacency::undirected(edge_storage, vertices_count);
+    let partition = two_color(&adjacency).map_err(|cycle| Cycle{vertices: cycle.into_iter().map(VHandleOf::<Edges>::from_index).collect()})?;
+    let left: Vec<usize> = (0..vertices_count).filter(|vertex| !partition.right[*vertex]).collect();
+    let mut mates = vec![UNMATCHED; vertices_count];
+    let mut layers = vec![usize::MAX; vertices_count];
+    let mut next_edges = vec![0usize; vertices_count];
+    let mut queue = VecDeque::new();
+    let mut stack: Vec<usize> = Vec::new();
+    let mut through: Vec<usize> = Vec::new();
+
+    loop {
+        // Layer the left vertices by alternating distance from the free ones
+        let mut found_free = false;
+        for &vertex in &left {
+            if mates[vertex] == UNMATCHED {
+                layers[vertex] = 0;
+                queue.push_back(vertex);
+            } else {
+                layers[vertex] = usize::MAX;
+            }
+        }
+        while let Some(vertex) = queue.pop_front() {
+            for &neighbor in adjacency.of(vertex) {
+                let mate = mates[neighbor];
+                if mate == UNMATCHED {
+                    found_free = true;
+                } else if layers[mate] == usize::MAX {
+                    layers[mate] = layers[vertex] + 1;
+                    queue.push_back(mate);
+                }
+            }
+        }
+        if !found_free {
+            break;
+        }
+
+        // Vertex disjoint shortest augmenting paths along the layers
+        for &vertex in &left {
+            next_edges[vertex] = adjacency.first[vertex];
+        }
+        for &root in &left {
+            if mates[root] != UNMATCHED {
+                continue;
+            }
+            stack.clear();
+            through.clear();
+            stack.push(root);
+            while let Some(&vertex) = stack.last() {
+                if next_edges[vertex] == adjacency.first[vertex + 1] {
+                    layers[vertex] = usize::MAX;
+                    stack.pop();
+                    through.pop();
+                    continue;
+                }
+                let neighbor = adjacency.neighbors[next_edges[vertex]];
+                next_edges[vertex] += 1;
+                let mate = mates[neighbor];
+                if mate == UNMATCHED {
+                    through.push(neighbor);
+                    for (left_vertex, right_vertex) in stack.iter().zip(&through) {
+                        mates[*left_vertex] = *right_vertex;
+                        mates[*right_vertex] = *left_vertex;
+                    }
+                    break;
+                }
+                if layers[mate] == layers[vertex] + 1 {
+                    through.push(neighbor);
+                    stack.push(mate);
+                }
+            }
+        }
+    }
+
+    // König: vertices reachable from free left vertices over alternating paths
+    let mut reached = vec![false; vertices_count];
+    for &vertex in &left {
+        if mates[vertex] == UNMATCHED {
+            reached[vertex] = true;
+            queue.push_back(vertex);
+        }
+    }
+    while let Some(vertex) = queue.pop_front() {
+        for &neighbor in adjacency.of(vertex) {
+            if reached[neighbor] {
+                continue;
+            }
+            reached[neighbor] = true;
+            let mate = mates[neighbor];
+            if mate != UNMATCHED && !reached[mate] {
+                reached[mate] = true;
+                queue.push_back(mate);
+            }
+        }
+    }
+    let vertex_cover = (0..vertices_count)
+        .filter(|vertex| partition.right[*vertex] == reached[*vertex] && adjacency.first[*vertex] != adjacency.first[*vertex + 1])
+        .map(VHandleOf::<Edges>::from_index)
+        .collect();
+
+    return Ok(BipartiteMatching{
+        matching: Matching::from_mates(&mates),
+        partition,
+        vertex_cover,
+    });
+}
+
+/// Maximum cardinality matching of a general graph with Edmonds' blossom algorithm in O(V^3), ignoring edge
+/// directions. Odd cycles are contracted into their base while searching for augmenting paths.
+pub fn maximum_matching<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Matching<VHandleOf<Edges>> {
+    profile_fn!(maximum_matching);
+    let adjacency = Adjacency::undirected(edge_storage, vertices_count);
+    let mut search = BlossomSearch{
+        adjacency: &adjacency,
+        mates: vec![UNMATCHED; vertices_count],
+        parents: vec![UNMATCHED; vertices_count],
+        bases: (0..vertices_count).collect(),
+        used: vec![false; vertices_count],
+        in_blossom: vec![false; vertices_count],
+        on_path: vec![false; vertices_count],
+        queue: VecDeque::new(),
+    };
+
+    // A greedy start leaves far fewer roots to search from
+    for vertex in 0..vertices_count {
+        if search.mates[vertex] != UNMATCHED {
+            continue;
+        }
+        if let Some(&neighbor) = adjacency.of(vertex).iter().find(|neighbor| **neighbor != vertex && search.mates[**neighbor] == UNMATCHED) {
+            search.mates[vertex] = neighbor;
+            search.mates[neighbor] = vertex;
+        }
+    }
+    for root in 0..vertices_count {
+        if search.mates[root] != UNMATCHED {
+            continue;
+        }
+        if let Some(mut vertex) = search.augmenting_path(root) {
+            // Flip the matched and unmatched edges along the path back to the root
+            while vertex != UNMATCHED {
+                let parent = search.parents[vertex];
+                let next = search.mates[parent];
+                search.mates[vertex] = parent;
+                search.mates[parent] = vertex;
+                vertex = next;
+            }
+        }
+    }
+    return Matching::from_mates(&search.mates);
+}
+
+struct BlossomSearch<'a> {
+    adjacency: &'a Adjacency,
+    mates: Vec<usize>,
+    parents: Vec<usize>,
+    bases: Vec<usize>,
+    used: Vec<bool>,
+    in_blossom: Vec<bool>,
+    on_path: Vec<bool>,
+    queue: VecDeque<usize>,
+}
+
+impl<'a> BlossomSearch<'a> {
+    /// Grows an alternating tree from `root` and returns the free vertex ending an augmenting path, if any.
+    fn augmenting_path(&mut self, root: usize) -> Option<usize> {
+        let vertices_count = self.mates.len();
+        self.used.fill(false);
+        self.parents.fill(UNMATCHED);
+        for vertex in 0..vertices_count {
+            self.bases[vertex] = vertex;
+        }
+        self.queue.clear();
+        self.used[root] = true;
+        self.queue.push_back(root);
+
+        while let Some(vertex) = self.queue.pop_front() {
+            for &neighbor in self.adjacency.of(vertex) {
+                if self.bases[vertex] == self.bases[neighbor] || self.mates[vertex] == neighbor {
+                    continue;
+                }
+                if neighbor == root || (self.mates[neighbor] != UNMATCHED && self.parents[self.mates[neighbor]] != UNMATCHED) {
+                    // Both ends are outer vertices, contract the odd cycle
+                    let base = self.common_base(vertex, neighbor);
+                    self.in_blossom.fill(false);
+                    self.mark_path(vertex, base, neighbor);
+                    self.mark_path(neighbor, base, vertex);
+                    for other in 0..vertices_count {
+                        if self.in_blossom[self.bases[other]] {
+                            self.bases[other] = base;
+                            if !self.used[other] {
+                                self.used[other] = true;
+                                self.queue.push_back(other);
+                            }
+                        }
+                    }
+                } else if self.parents[neighbor] == UNMATCHED {
+                    self.parents[neighbor] = vertex;
+                    let mate = self.mates[neighbor];
+                    if mate == UNMATCHED {
+                        return Some(neighbor);
+                    }
+                    self.used[mate] = true;
+                    self.queue.push_back(mate);
+                }
+            }
+        }
+        return None;
+    }
+
+    fn common_base(&mut self, mut a: usize, mut b: usize) -> usize {
+        self.on_path.fill(false);
+        loop {
+            a = self.bases[a];
+            self.on_path[a] = true;
+            if self.mates[a] == UNMATCHED {
+                break;
+            }
+            a = self.parents[self.mates[a]];
+        }
+        loop {
+            b = self.bases[b];
+            if self.on_path[b] {
+                return b;
+            }
+            b = self.parents[self.mates[b]];
+        }
+    }
+
+    fn mark_path(&mut self, mut vertex: usize, base: usize, mut child: usize) {
+        while self.bases[vertex] != base {
+            let mate = self.mates[vertex];
+            self.in_blossom[self.bases[vertex]] = true;
+            self.in_blossom[self.bases[mate]] = true;
+            self.parents[vertex] = child;
+            child = mate;
+            vertex = self.parents[mate];
+        }
+    }
+}
+
+/// Matching with the largest total weight, `weight` is the sum of the matched edge weights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightedMatching<H> {
+    pub matching: Matching<H>,
+    pub weight: Cost,
+}
+
+/// Maximum weight matching of a general graph with the primal-dual blossom algorithm (Edmonds, Galil) in O(V^3),
+/// ignoring edge directions. With `max_cardinality` the heaviest among the matchings of maximum size is returned.
+/// Of parallel edges only the heaviest counts and self-loops are ignored.
+pub fn maximum_weight_matching<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize, max_cardinality: bool) -> WeightedMatching<VHandleOf<Edges>> {
+    profile_fn!(maximum_weight_matching);
+    let mut links: Vec<(usize, usize, Cost)> = Vec::new();
+    for vertex in 0..vertices_count {
+        for edge in edge_storage.edges(VHandleOf::<Edges>::from_index(vertex)) {
+            let target = edge.vh().index();
+            if target != vertex {
+                links.push((vertex.min(target), vertex.max(target), edge.wgt().into()));
+            }
+        }
+    }
+    links.sort_unstable_by(|a, b| (a.0, a.1, b.2).cmp(&(b.0, b.1, a.2)));
+    links.dedup_by_key(|link| (link.0, link.1));
+
+    let mut blossom = WeightedBlossom::new(vertices_count, &links);
+    blossom.solve(max_cardinality);
+    let mut weight = 0;
+    let mut mates = vec![UNMATCHED; vertices_count];
+    for vertex in 0..vertices_count {
+        let endpoint = blossom.mates[vertex];
+        if endpoint != UNMATCHED {
+            mates[vertex] = blossom.endpoints[endpoint];
+            if vertex < mates[vertex] {
+                weight += links[endpoint / 2].2;
+            }
+        }
+    }
+    return WeightedMatching{
+        matching: Matching::from_mates(&mates),
+        weight,
+    };
+}
+
+const FREE: u8 = 0;
+const OUTER: u8 = 1;
+const INNER: u8 = 2;
+const SCANNED: u8 = 4;
+
+/// State of the primal-dual search. Edge `k` has the endpoints `2k` and `2k + 1`, blossoms use the indices
+/// `vertices_count..2 * vertices_count`, single vertices are trivial blossoms with their own index.
+/// Weights are doubled so every dual variable and slack stays integral.
+struct WeightedBlossom {
+    vertices_count: usize,
+    links: Vec<(usize, usize, Cost)>,
+    endpoints: Vec<usize>,
+    neighbor_ends: Vec<Vec<usize>>,
+    mates: Vec<usize>,
+    labels: Vec<u8>,
+    label_ends: Vec<usize>,
+    in_blossom: Vec<usize>,
+    blossom_parents: Vec<usize>,
+    blossom_children: Vec<Vec<usize>>,
+    blossom_bases: Vec<usize>,
+    blossom_ends: Vec<Vec<usize>>,
+    best_edges: Vec<usize>,
+    blossom_best_edges: Vec<Option<Vec<usize>>>,
+    unused_blossoms: Vec<usize>,
+    duals: Vec<Cost>,
+    allowed: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+enum Delta {
+    Finish,
+    Edge(usize),
+    OuterEdge(usize),
+    Expand(usize),
+}
+
+impl WeightedBlossom {
+    fn new(vertices_count: usize, links: &[(usize, usize, Cost)]) -> Self {
+        let links: Vec<(usize, usize, Cost)> = links.iter().map(|(a, b, weight)| (*a, *b, 2 * weight)).collect();
+        let max_weight = links.iter().map(|link| link.2).max().unwrap_or(0).max(0);
+        let mut neighbor_ends = vec![Vec::new(); vertices_count];
+        let mut endpoints = Vec::with_capacity(2 * links.len());
+        for (index, (a, b, _)) in links.iter().enumerate() {
+            endpoints.push(*a);
+            endpoints.push(*b);
+            neighbor_ends[*a].push(2 * index + 1);
+            neighbor_ends[*b].push(2 * index);
+        }
+        let blossoms_count = 2 * vertices_count;
+        let mut duals = vec![max_weight; vertices_count];
+        duals.resize(blossoms_count, 0);
+        return WeightedBlossom{
+            vertices_count,
+            allowed: vec![false; links.len()],
+            links,
+            endpoints,
+            neighbor_ends,
+            mates: vec![UNMATCHED; vertices_count],
+            labels: vec![FREE; blossoms_count],
+            label_ends: vec![UNMATCHED; blossoms_count],
+            in_blossom: (0..vertices_count).collect(),
+            blossom_parents: vec![UNMATCHED; blossoms_count],
+            blossom_children: vec![Vec::new(); blossoms_count],
+            blossom_bases: (0..vertices_count).chain(std::iter::repeat_n(UNMATCHED, vertices_count)).collect(),
+            blossom_ends: vec![Vec::new(); blossoms_count],
+            best_edges: vec![UNMATCHED; blossoms_count],
+            blossom_best_edges: vec![None; blossoms_count],
+            unused_blossoms: (vertices_count..blossoms_count).rev().collect(),
+            duals,
+            queue: Vec::new(),
+        };
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn slack(&self, edge: usize) -> Cost {
+        let (a, b, weight) = self.links[edge];
+        return self.duals[a] + self.duals[b] - weight;
+    }
+
+    fn leaves(&self, blossom: usize, leaves: &mut Vec<usize>) {
+        if blossom < self.vertices_count {
+            leaves.push(blossom);
+            return;
+        }
+        for child in &self.blossom_children[blossom] {
+            self.leaves(*child, leaves);
+        }
+    }
+
+    fn leaves_of(&self, blossom: usize) -> Vec<usize> {
+        let mut leaves = Vec::new();
+        self.leaves(blossom, &mut leaves);
+        return leaves;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn child_at(&self, blossom: usize, index: isize) -> usize {
+        let children = &self.blossom_children[blossom];
+        return children[index.rem_euclid(children.len() as isize) as usize];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn end_at(&self, blossom: usize, index: isize) -> usize {
+        let ends = &self.blossom_ends[blossom];
+        return ends[index.rem_euclid(ends.len() as isize) as usize];
+    }
+
+    fn assign_label(&mut self, vertex: usize, label: u8, endpoint: usize) {
+        let mut vertex = vertex;
+        let mut label = label;
+        let mut endpoint = endpoint;
+        loop {
+            let blossom = self.in_blossom[vertex];
+            self.labels[vertex] = label;
+            self.labels[blossom] = label;
+            self.label_ends[vertex] = endpoint;
+            self.label_ends[blossom] = endpoint;
+            self.best_edges[vertex] = UNMATCHED;
+            self.best_edges[blossom] = UNMATCHED;
+            if label == OUTER {
+                let leaves = self.leaves_of(blossom);
+                self.queue.extend(leaves);
+                return;
+            }
+            // An inner blossom passes the outer label on to the mate of its base
+            let mate = self.mates[self.blossom_bases[blossom]];
+            vertex = self.endpoints[mate];
+            label = OUTER;
+            endpoint = mate ^ 1;
+        }
+    }
+
+    /// Walks up from both ends of an edge between outer vertices. Returns the base of a new blossom or
+    /// `UNMATCHED` when the trees differ and the edge closes an augmenting path.
+    fn scan_blossom(&mut self, a: usize, b: usize) -> usize {
+        let mut path = Vec::new();
+        let mut base = UNMATCHED;
+        let (mut v, mut w) = (a, b);
+        while v != UNMATCHED || w != UNMATCHED {
+            let mut blossom = self.in_blossom[v];
+            if self.labels[blossom] & SCANNED != 0 {
+                base = self.blossom_bases[blossom];
+                break;
+            }
+            path.push(blossom);
+            self.labels[blossom] = OUTER | SCANNED;
+            if self.label_ends[blossom] == UNMATCHED {
+                v = UNMATCHED;
+            } else {
+                v = self.endpoints[self.label_ends[blossom]];
+                blossom = self.in_blossom[v];
+                v = self.endpoints[self.label_ends[blossom]];
+            }
+            if w != UNMATCHED {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for blossom in path {
+            self.labels[blossom] = OUTER;
+        }
+        return base;
+    }
+
+    fn add_blossom(&mut self, base: usize, edge: usize) {
+        let (mut v, mut w, _) = self.links[edge];
+        let base_blossom = self.in_blossom[base];
+        let mut v_blossom = self.in_blossom[v];
+        let mut w_blossom = self.in_blossom[w];
+        let blossom = self.unused_blossoms.pop().unwrap();
+        self.blossom_bases[blossom] = base;
+        self.blossom_parents[blossom] = UNMATCHED;
+        self.blossom_parents[base_blossom] = blossom;
+
+        let mut children = Vec::new();
+        let mut ends = Vec::new();
+        while v_blossom != base_blossom {
+            self.blossom_parents[v_blossom] = blossom;
+            children.push(v_blossom);
+            ends.push(self.label_ends[v_blossom]);
+            v = self.endpoints[self.label_ends[v_blossom]];
+            v_blossom = self.in_blossom[v];
+        }
+        children.push(base_blossom);
+        children.reverse();
+        ends.reverse();
+        ends.push(2 * edge);
+        while w_blossom != base_blossom {
+            self.blossom_parents[w_blossom] = blossom;
+            children.push(w_blossom);
+            ends.push(self.label_ends[w_blossom] ^ 1);
+            w = self.endpoints[self.label_ends[w_blossom]];
+            w_blossom = self.in_blossom[w];
+        }
+        self.blossom_children[blossom] = children.clone();
+        self.blossom_ends[blossom] = ends;
+
+        self.labels[blossom] = OUTER;
+        self.label_ends[blossom] = self.label_ends[base_blossom];
+        self.duals[blossom] = 0;
+        for leaf in self.leaves_of(blossom) {
+            if self.labels[self.in_blossom[leaf]] == INNER {
+                // Former inner vertices become outer and have to be scanned
+                self.queue.push(leaf);
+            }
+            self.in_blossom[leaf] = blossom;
+        }
+
+        // Keep the least slack edge to every other outer blossom
+        let mut best_to = vec![UNMATCHED; 2 * self.vertices_count];
+        for child in children {
+            let edge_lists: Vec<Vec<usize>> = match self.blossom_best_edges[child].take() {
+                Some(edges) => vec![edges],
+                None => self.leaves_of(child).iter().map(|leaf| self.neighbor_ends[*leaf].iter().map(|end| end / 2).collect()).collect(),
+            };
+            for edges in edge_lists {
+                for link in edges {
+                    let (i, mut j, _) = self.links[link];
+                    if self.in_blossom[j] == blossom {
+                        j = i;
+                    }
+                    let j_blossom = self.in_blossom[j];
+                    if j_blossom != blossom && self.labels[j_blossom] == OUTER
+                        && (best_to[j_blossom] == UNMATCHED || self.slack(link) < self.slack(best_to[j_blossom])) {
+                        best_to[j_blossom] = link;
+                    }
+                }
+            }
+            self.best_edges[child] = UNMATCHED;
+        }
+        let best: Vec<usize> = best_to.into_iter().filter(|link| *link != UNMATCHED).collect();
+        self.best_edges[blossom] = UNMATCHED;
+        for link in &best {
+            if self.best_edges[blossom] == UNMATCHED || self.slack(*link) < self.slack(self.best_edges[blossom]) {
+                self.best_edges[blossom] = *link;
+            }
+        }
+        self.blossom_best_edges[blossom] = Some(best);
+    }
+
+    fn expand_blossom(&mut self, blossom: usize, end_stage: bool) {
+        let children = self.blossom_children[blossom].clone();
+        for child in &children {
+            self.blossom_parents[*child] = UNMATCHED;
+            if *child < self.vertices_count {
+                self.in_blossom[*child] = *child;
+            } else if end_stage && self.duals[*child] == 0 {
+                self.expand_blossom(*child, end_stage);
+            } else {
+                for leaf in self.leaves_of(*child) {
+                    self.in_blossom[leaf] = *child;
+                }
+            }
+        }
+
+        if !end_stage && self.labels[blossom] == INNER {
+            // Relabel the even length path from the entry child to the base, the rest becomes free
+            let entry_child = self.in_blossom[self.endpoints[self.label_ends[blossom] ^ 1]];
+            let mut index = children.iter().position(|child| *child == entry_child).unwrap() as isize;
+            let (step, trick) = if index & 1 == 1 {
+                index -= children.len() as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut endpoint = self.label_ends[blossom];
+            while index != 0 {
+                let child_end = self.end_at(blossom, index - trick);
+                self.labels[self.endpoints[endpoint ^ 1]] = FREE;
+                self.labels[self.endpoints[child_end ^ trick as usize ^ 1]] = FREE;
+                self.assign_label(self.endpoints[endpoint ^ 1], INNER, endpoint);
+                self.allowed[child_end / 2] = true;
+                index += step;
+                endpoint = self.end_at(blossom, index - trick) ^ trick as usize;
+                self.allowed[endpoint / 2] = true;
+                index += step;
+            }
+            let base_child = self.child_at(blossom, index);
+            let entry = self.endpoints[endpoint ^ 1];
+            self.labels[entry] = INNER;
+            self.labels[base_child] = INNER;
+            self.label_ends[entry] = endpoint;
+            self.label_ends[base_child] = endpoint;
+            self.best_edges[base_child] = UNMATCHED;
+            index += step;
+            while self.child_at(blossom, index) != entry_child {
+                let child = self.child_at(blossom, index);
+                index += step;
+                if self.labels[child] == OUTER {
+                    continue;
+                }
+                let labeled = self.leaves_of(child).into_iter().find(|leaf| self.labels[*leaf] != FREE);
+                if let Some(leaf) = labeled {
+                    self.labels[leaf] = FREE;
+                    let base_mate = self.endpoints[self.mates[self.blossom_bases[child]]];
+                    self.labels[base_mate] = FREE;
+                    self.assign_label(leaf, INNER, self.label_ends[leaf]);
+                }
+            }
+        }
+
+        self.labels[blossom] = FREE;
+        self.label_ends[blossom] = UNMATCHED;
+        self.blossom_children[blossom] = Vec::new();
+        self.blossom_ends[blossom] = Vec::new();
+        self.blossom_bases[blossom] = UNMATCHED;
+        self.blossom_best_edges[blossom] = None;
+        self.best_edges[blossom] = UNMATCHED;
+        self.unused_blossoms.push(blossom);
+    }
+
+    /// Swaps matched and unmatched edges on the even path from `vertex` to the base and makes `vertex` the new base.
+    fn augment_blossom(&mut self, blossom: usize, vertex: usize) {
+        let mut child = vertex;
+        while self.blossom_parents[child] != blossom {
+            child = self.blossom_parents[child];
+        }
+        if child >= self.vertices_count {
+            self.augment_blossom(child, vertex);
+        }
+        let start = self.blossom_children[blossom].iter().position(|other| *other == child).unwrap();
+        let mut index = start as isize;
+        let (step, trick) = if index & 1 == 1 {
+            index -= self.blossom_children[blossom].len() as isize;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while index != 0 {
+            index += step;
+            let child = self.child_at(blossom, index);
+            let endpoint = self.end_at(blossom, index - trick) ^ trick as usize;
+            if child >= self.vertices_count {
+                self.augment_blossom(child, self.endpoints[endpoint]);
+            }
+            index += step;
+            let child = self.child_at(blossom, index);
+            if child >= self.vertices_count {
+                self.augment_blossom(child, self.endpoints[endpoint ^ 1]);
+            }
+            self.mates[self.endpoints[endpoint]] = endpoint ^ 1;
+            self.mates[self.endpoints[endpoint ^ 1]] = endpoint;
+        }
+        self.blossom_children[blossom].rotate_left(start);
+        self.blossom_ends[blossom].rotate_left(start);
+        self.blossom_bases[blossom] = self.blossom_bases[self.blossom_children[blossom][0]];
+        debug_assert_eq!(self.blossom_bases[blossom], vertex);
+    }
+
+    fn augment_matching(&mut self, edge: usize) {
+        let (v, w, _) = self.links[edge];
+        for (start, first_end) in [(v, 2 * edge + 1), (w, 2 * edge)] {
+            let mut vertex = start;
+            let mut endpoint = first_end;
+            loop {
+                let blossom = self.in_blossom[vertex];
+                if blossom >= self.vertices_count {
+                    self.augment_blossom(blossom, vertex);
+                }
+                self.mates[vertex] = endpoint;
+                if self.label_ends[blossom] == UNMATCHED {
+                    // Reached a free root
+                    break;
+                }
+                let inner = self.endpoints[self.label_ends[blossom]];
+                let inner_blossom = self.in_blossom[inner];
+                vertex = self.endpoints[self.label_ends[inner_blossom]];
+                let other = self.endpoints[self.label_ends[inner_blossom] ^ 1];
+                if inner_blossom >= self.vertices_count {
+                    self.augment_blossom(inner_blossom, other);
+                }
+                self.mates[other] = self.label_ends[inner_blossom];
+                endpoint = self.label_ends[inner_blossom] ^ 1;
+            }
+        }
+    }
+
+    /// Scans the queued outer vertices, returns whether the matching grew.
+    fn scan(&mut self) -> bool {
+        while let Some(vertex) = self.queue.pop() {
+            for end_index in 0..self.neighbor_ends[vertex].len() {
+                let endpoint = self.neighbor_ends[vertex][end_index];
+                let edge = endpoint / 2;
+                let neighbor = self.endpoints[endpoint];
+                if self.in_blossom[vertex] == self.in_blossom[neighbor] {
+                    continue;
+                }
+                let mut slack = 0;
+                if !self.allowed[edge] {
+                    slack = self.slack(edge);
+                    if slack <= 0 {
+                        self.allowed[edge] = true;
+                    }
+                }
+                let neighbor_label = self.labels[self.in_blossom[neighbor]];
+                if self.allowed[edge] {
+                    if neighbor_label == FREE {
+                        self.assign_label(neighbor, INNER, endpoint ^ 1);
+                    } else if neighbor_label == OUTER {
+                        let base = self.scan_blossom(vertex, neighbor);
+                        if base != UNMATCHED {
+                            self.add_blossom(base, edge);
+                        } else {
+                            self.augment_matching(edge);
+                            return true;
+                        }
+                    } else if self.labels[neighbor] == FREE {
+                        self.labels[neighbor] = INNER;
+                        self.label_ends[neighbor] = endpoint ^ 1;
+                    }
+                } else if neighbor_label == OUTER {
+                    let blossom = self.in_blossom[vertex];
+                    if self.best_edges[blossom] == UNMATCHED || slack < self.slack(self.best_edges[blossom]) {
+                        self.best_edges[blossom] = edge;
+                    }
+                } else if self.labels[neighbor] == FREE
+                    && (self.best_edges[neighbor] == UNMATCHED || slack < self.slack(self.best_edges[neighbor])) {
+                    self.best_edges[neighbor] = edge;
+                }
+            }
+        }
+        return false;
+    }
+
+    /// Picks the largest dual change that keeps every slack non-negative and applies it.
+    fn adjust_duals(&mut self, max_cardinality: bool) -> Delta {
+        let vertices_count = self.vertices_count;
+        let mut delta_kind = None;
+        let mut delta = 0;
+        if !max_cardinality {
+            delta_kind = Some(Delta::Finish);
+            delta = self.duals[..vertices_count].iter().copied().min().unwrap_or(0);
+        }
+        for vertex in 0..vertices_count {
+            if self.labels[self.in_blossom[vertex]] == FREE && self.best_edges[vertex] != UNMATCHED {
+                let slack = self.slack(self.best_edges[vertex]);
+                if delta_kind.is_none() || slack < delta {
+                    delta = slack;
+                    delta_kind = Some(Delta::Edge(self.best_edges[vertex]));
+                }
+            }
+        }
+        for blossom in 0..2 * vertices_count {
+            if self.blossom_parents[blossom] == UNMATCHED && self.labels[blossom] == OUTER && self.best_edges[blossom] != UNMATCHED {
+                let slack = self.slack(self.best_edges[blossom]) / 2;
+                if delta_kind.is_none() || slack < delta {
+                    delta = slack;
+                    delta_kind = Some(Delta::OuterEdge(self.best_edges[blossom]));
+                }
+            }
+        }
+        for blossom in vertices_count..2 * vertices_count {
+            if self.blossom_bases[blossom] != UNMATCHED && self.blossom_parents[blossom] == UNMATCHED && self.labels[blossom] == INNER
+                && (delta_kind.is_none() || self.duals[blossom] < delta) {
+                delta = self.duals[blossom];
+                delta_kind = Some(Delta::Expand(blossom));
+            }
+        }
+        let delta_kind = delta_kind.unwrap_or_else(|| {
+            // Maximum cardinality reached, no further improvement is possible
+            delta = self.duals[..vertices_count].iter().copied().min().unwrap_or(0).max(0);
+            return Delta::Finish;
+        });
+
+        for vertex in 0..vertices_count {
+            match self.labels[self.in_blossom[vertex]] {
+                OUTER => self.duals[vertex] -= delta,
+                INNER => self.duals[vertex] += delta,
+                _ => {}
+            }
+        }
+        for blossom in vertices_count..2 * vertices_count {
+            if self.blossom_bases[blossom] != UNMATCHED && self.blossom_parents[blossom] == UNMATCHED {
+                match self.labels[blossom] {
+                    OUTER => self.duals[blossom] += delta,
+                    INNER => self.duals[blossom] -= delta,
+                    _ => {}
+                }
+            }
+        }
+        return delta_kind;
+    }
+
+    fn solve(&mut self, max_cardinality: bool) {
+        let vertices_count = self.vertices_count;
+        for _ in 0..vertices_count {
+            // Every stage grows the matching by one edge or proves it maximal
+            self.labels.fill(FREE);
+            self.best_edges.fill(UNMATCHED);
+            for blossom in vertices_count..2 * vertices_count {
+                self.blossom_best_edges[blossom] = None;
+            }
+            self.allowed.fill(false);
+            self.queue.clear();
+            for vertex in 0..vertices_count {
+                if self.mates[vertex] == UNMATCHED && self.labels[self.in_blossom[vertex]] == FREE {
+                    self.assign_label(vertex, OUTER, UNMATCHED);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                if self.scan() {
+                    augmented = true;
+                    break;
+                }
+                match self.adjust_duals(max_cardinality) {
+                    Delta::Finish => break,
+                    Delta::Edge(edge) => {
+                        self.allowed[edge] = true;
+                        let (a, b, _) = self.links[edge];
+                        self.queue.push(if self.labels[self.in_blossom[a]] == FREE { b } else { a });
+                    }
+                    Delta::OuterEdge(edge) => {
+                        self.allowed[edge] = true;
+                        self.queue.push(self.links[edge].0);
+                    }
+                    Delta::Expand(blossom) => self.expand_blossom(blossom, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+
+            // Outer blossoms with a zero dual are no longer needed
+            for blossom in vertices_count..2 * vertices_count {
+                if self.blossom_parents[blossom] == UNMATCHED && self.blossom_bases[blossom] != UNMATCHED
+                    && self.labels[blossom] == OUTER && self.duals[blossom] == 0 {
+                    self.expand_blossom(blossom, true);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use firestorm::{profile_fn, profile_section};
+use crate::algorithms::flow::{Flow, MaxFlow, Residual};
+use crate::algorithms::shortest_path::{predecessor_cycle, Cost, NegativeCycle};
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeStore, PayloadStore, VHandleOf};
+
+/// A flow of minimum total cost among all flows of the same value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinCostFlow<H> {
+    pub flow: MaxFlow<H>,
+    pub cost: Cost,
+}
+
+/// Maximum flow of minimum cost from `source` to `sink`. Edge weights are the capacities and the payload of an
+/// edge is its cost per unit of flow, see `PayloadStore::connect_weighted_with`.
+/// Fails with the cycle if a cycle with capacity and a negative total cost is reachable from `source`.
+pub fn min_cost_max_flow<Edges>(edge_storage: &Edges, source: VHandleOf<Edges>, sink: VHandleOf<Edges>, vertices_count: usize)
+    -> Result<MinCostFlow<VHandleOf<Edges>>, NegativeCycle<VHandleOf<Edges>>>
+where Edges: PayloadStore, Edges::Payload: Copy + Into<Cost> {
+    profile_fn!(min_cost_max_flow);
+    return min_cost_flow(edge_storage, source, sink, vertices_count, Flow::MAX, |vertex, offset| edge_storage.payloads(vertex)[offset].into());
+}
+
+/// Sends up to `limit` units from `source` to `sink` at minimum cost with successive shortest paths.
+/// `cost(vertex, offset)` is the cost per unit over the `offset`-th edge of `vertex`. Costs may be negative,
+/// a cycle with capacity and a negative total cost reachable from `source` is returned as the error.
+pub fn min_cost_flow<Edges, CostFunc>(edge_storage: &Edges, source: VHandleOf<Edges>, sink: VHandleOf<Edges>, vertices_count: usize, limit: Flow,
+                                      mut cost: CostFunc) -> Result<MinCostFlow<VHandleOf<Edges>>, NegativeCycle<VHandleOf<Edges>>>
+where Edges: EdgeStore, CostFunc: FnMut(VHandleOf<Edges>, usize) -> Cost {
+    profile_fn!(min_cost_flow);
+    let mut residual = Residual::new(edge_storage, vertices_count);
+    let mut arc_costs = vec![0 as Cost; residual.heads.len()];
+    for vertex in 0..vertices_count {
+        let handle = VHandleOf::<Edges>::from_index(vertex);
+        for offset in 0..edge_storage.len(handle) {
+            let arc = residual.edge_arcs[residual.edge_offsets[vertex] + offset];
+            arc_costs[arc] = cost(handle, offset);
+            arc_costs[residual.reverse[arc]] = -arc_costs[arc];
+        }
+    }
+    let (source, sink) = (source.index(), sink.index());
+    let mut value = 0;
+    let mut total_cost = 0;
+    if source == sink {
+        return Ok(MinCostFlow{flow: residual.into_max_flow(source, value), cost: total_cost});
+    }
+
+    // Potentials from a Bellman-Ford pass make every reduced cost non negative, so Dijkstra can take over
+    let mut potentials = initial_potentials(&residual, &arc_costs, source)?;
+    let mut distances = vec![Cost::MAX; vertices_count];
+    let mut parent_arcs = vec![usize::MAX; vertices_count];
+    let mut queue = BinaryHeap::new();
+
+    while value < limit {
+        profile_section!(min_cost_flow_augment);
+        distances.fill(Cost::MAX);
+        distances[source] = 0;
+        queue.push(Reverse((0, source)));
+        while let Some(Reverse((distance, vertex))) = queue.pop() {
+            if distance > distances[vertex] {
+                continue;
+            }
+            for arc in residual.arcs(vertex) {
+                if residual.capacities[arc] == 0 {
+                    continue;
+                }
+                let head = residual.heads[arc];
+                let next_distance = distance + arc_costs[arc] + potentials[vertex] - potentials[head];
+                if next_distance < distances[head] {
+                    distances[head] = next_distance;
+                    parent_arcs[head] = arc;
+                    queue.push(Reverse((next_distance, head)));
+                }
+            }
+        }
+        if distances[sink] == Cost::MAX {
+            break;
+        }
+        for vertex in 0..vertices_count {
+            if distances[vertex] != Cost::MAX {
+                potentials[vertex] += distances[vertex];
+            }
+        }
+
+        let mut amount = limit - value;
+        let mut vertex = sink;
+        while vertex != source {
+            let arc = parent_arcs[vertex];
+            amount = amount.min(residual.capacities[arc]);
+            vertex = residual.heads[residual.reverse[arc]];
+        }
+        let mut vertex = sink;
+        while vertex != source {
+            let arc = parent_arcs[vertex];
+            residual.push(arc, amount);
+            total_cost += amount * arc_costs[arc];
+            vertex = residual.heads[residual.reverse[arc]];
+        }
+        value += amount;
+    }
+    return Ok(MinCostFlow{flow: residual.into_max_flow(source, value), cost: total_cost});
+}
+
+/// Shortest path costs from `source` over arcs with capacity, 0 for vertices it cannot reach.
+/// Relaxes like `bellman_ford` and fails with the cycle once a path would need `vertices_count` arcs.
+fn initial_potentials<H: HandleIndex>(residual: &Residual, arc_costs: &[Cost], source: usize) -> Result<Vec<Cost>, NegativeCycle<H>> {
+    let vertices_count = residual.vertices_count();
+    let mut potentials = vec![Cost::MAX; vertices_count];
+    let mut predecessors: Vec<Option<H>> = vec![None; vertices_count];
+    let mut path_lengths = vec![0usize; vertices_count];
+    let mut queued = vec![false; vertices_count];
+    let mut queue = VecDeque::new();
+    potentials[source] = 0;
+    queue.push_back(source);
+    while let Some(vertex) = queue.pop_front() {
+        queued[vertex] = false;
+        for arc in residual.arcs(vertex) {
+            let head = residual.heads[arc];
+            if residual.capacities[arc] == 0 || potentials[vertex] + arc_costs[arc] >= potentials[head] {
+                continue;
+            }
+            potentials[head] = potentials[vertex] + arc_costs[arc];
+            predecessors[head] = Some(H::from_index(vertex));
+            path_lengths[head] = path_lengths[vertex] + 1;
+            if path_lengths[head] >= vertices_count {
+                if let Some(cycle) = predecessor_cycle(&predecessors) {
+                    return Err(cycle);
+                }
+            }
+            if !queued[head] {
+                queued[head] = true;
+                queue.push_back(head);
+            }
+        }
+    }
+    for potential in &mut potentials {
+        if *potential == Cost::MAX {
+            *potential = 0;
+        }
+    }
+    return Ok(potentials);
+}
+
+/// Assignment of rows to distinct columns, `columns[row]` is the column picked for `row`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub columns: Vec<usize>,
+    pub cost: Cost,
+}
+
+/// Minimum cost assignment with the Hungarian algorithm, O(rows^2 columns). `costs[row][column]` is the cost
+/// of giving `column` to `row`. Every row gets a column, so there must not be more rows than columns.
+/// Panics if the rows differ in length.
+pub fn hungarian(costs: &[Vec<Cost>]) -> Assignment {
+    profile_fn!(hungarian);
+    let rows = costs.len();
+    if rows == 0 {
+        return Assignment{columns: Vec::new(), cost: 0};
+    }
+    let columns = costs[0].len();
+    if let Some(row) = costs.iter().position(|row| row.len() != columns) {
+        panic!("Row {} has {} columns, expected {}", row, costs[row].len(), columns);
+    }
+    assert!(rows <= columns, "Cannot assign {} rows to {} columns", rows, columns);
+
+    // Potentials and matching are 1 based, index 0 is the virtual row being inserted
+    let mut row_potentials = vec![0 as Cost; rows + 1];
+    let mut column_potentials = vec![0 as Cost; columns + 1];
+    let mut row_of = vec![0usize; columns + 1];
+    let mut previous = vec![0usize; columns + 1];
+    let mut slack = vec![Cost::MAX; columns + 1];
+    let mut used = vec![false; columns + 1];
+
+    for row in 1..=rows {
+        row_of[0] = row;
+        let mut column = 0;
+        slack.fill(Cost::MAX);
+        used.fill(false);
+        loop {
+            used[column] = true;
+            let current_row = row_of[column];
+            let mut delta = Cost::MAX;
+            let mut next_column = 0;
+            for candidate in 1..=columns {
+                if used[candidate] {
+                    continue;
+                }
+                let reduced = costs[current_row - 1][candidate - 1] - row_potentials[current_row] - column_potentials[candidate];
+                if reduced < slack[candidate] {
+                    slack[candidate] = reduced;
+                    previous[candidate] = column;
+                }
+                if slack[candidate] < delta {
+                    delta = slack[candidate];
+                    next_column = candidate;
+                }
+            }
+            for candidate in 0..=columns {
+                if used[candidate] {
+                    row_potentials[row_of[candidate]] += delta;
+                    column_potentials[candidate] -= delta;
+                } else {
+                    slack[candidate] -= delta;
+                }
+            }
+            column = next_column;
+            if row_of[column] == 0 {
+                break;
+            }
+        }
+        // Flip the alternating path ending in the free column
+        while column != 0 {
+            let previous_column = previous[column];
+            row_of[column] = row_of[previous_column];
+            column = previous_column;
+        }
+    }
+
+    let mut assigned = vec![0usize; rows];
+    for column in 1..=columns {
+        if row_of[column] != 0 {
+            assigned[row_of[column] - 1] = column - 1;
+        }
+    }
+    let cost = assigned.iter().enumerate().map(|(row, column)| costs[row][*column]).sum();
+    return Assignment{columns: assigned, cost};
+}
+
+/// Minimum cost assignment between vertices of a graph. Every vertex in `rows` gets a distinct vertex of
+/// `columns` it has an edge to, the edge weight being the cost (the lightest one among parallel edges).
+/// `columns[row]` of the result indexes into `columns`. `None` if the edges do not allow every row to be assigned.
+pub fn hungarian_graph<Edges: EdgeStore>(edge_storage: &Edges, rows: &[VHandleOf<Edges>], columns: &[VHandleOf<Edges>]) -> Option<Assignment> {
+    profile_fn!(hungarian_graph);
+    if rows.len() > columns.len() {
+        return None;
+    }
+    const NOT_A_COLUMN: usize = usize::MAX;
+    let slots = columns.iter().map(|vertex| vertex.index() + 1).max().unwrap_or(0);
+    let mut column_of = vec![NOT_A_COLUMN; slots];
+    for (column, vertex) in columns.iter().enumerate() {
+        column_of[vertex.index()] = column;
+    }
+    let mut costs = vec![vec![None; columns.len()]; rows.len()];
+    for (row, vertex) in rows.iter().enumerate() {
+        for edge in edge_storage.edges(*vertex) {
+            let column = column_of.get(edge.vh().index()).copied().unwrap_or(NOT_A_COLUMN);
+            if column == NOT_A_COLUMN {
+                continue;
+            }
+            let weight: Cost = edge.wgt().into();
+            if costs[row][column].is_none_or(|current| weight < current) {
+                costs[row][column] = Some(weight);
+            }
+        }
+    }
+
+    // Missing edges cost more than any assignment over edges alone, so they are only picked when unavoidable
+    let linked_total: Cost = costs.iter().flatten().flatten().map(|cost| cost.abs()).sum();
+    let missing = 2 * linked_total + 1;
+    let dense: Vec<Vec<Cost>> = costs.iter().map(|row| row.iter().map(|cost| cost.unwrap_or(missing)).collect()).collect();
+    let assignment = hungarian(&dense);
+    if assignment.columns.iter().enumerate().any(|(row, column)| costs[row][*column].is_none()) {
+        return None;
+    }
+    return Some(assignment);
+}
@@ -0,0 +1,193 @@
+use std::alloc::{alloc, dealloc, Layout};
+use std::slice::from_raw_parts_mut;
+use firestorm::{profile_fn, profile_section};
+use crate::handles::EdgePacking;
+use crate::traits::{EdgeOf, EdgeStore, TraverseMarker, VHandleOf, VisitedSet};
+pub mod traversal;
+pub mod visited;
+pub mod shortest_path;
+pub mod all_pairs;
+pub mod topological;
+pub mod components;
+pub mod union_find;
+pub mod spanning_tree;
+pub mod flow;
+pub mod min_cost_flow;
+pub mod matching;
+pub mod pagerank;
+
+pub use traversal::{Bfs, Dfs, DfsEvent, Visit};
+pub use visited::{VisitedBits, VisitedEpochs};
+pub use shortest_path::{astar, astar_with, bellman_ford, dijkstra, dijkstra_to, Cost, NegativeCycle, Path, SearchOutcome, ShortestPaths};
+pub use all_pairs::{floyd_warshall, johnson, DistanceMatrix};
+pub use topological::{find_cycle, find_cycle_with, topological_sort, topological_sort_by_key, topological_sort_by_key_with, topological_sort_dfs, topological_sort_dfs_with, topological_sort_with, Cycle};
+pub use components::{condense, condense_with, strongly_connected_components, strongly_connected_components_with, weakly_connected_components, weakly_connected_components_with, Components};
+pub use union_find::UnionFind;
+pub use spanning_tree::{kruskal, kruskal_with, prim, prim_with, SpanningForest};
+pub use flow::{dinic, push_relabel, Flow, MaxFlow};
+pub use min_cost_flow::{hungarian, hungarian_graph, min_cost_flow, min_cost_max_flow, Assignment, MinCostFlow};
+pub use matching::{bipartition, hopcroft_karp, maximum_matching, maximum_weight_matching, BipartiteMatching, Bipartition, Matching, WeightedMatching};
+pub use pagerank::{pagerank, pagerank_with, personalized_pagerank, PageRank, PageRankSettings};
+pub enum ControlFlow {
+    Resume,
+    End,
+    Continue,
+}
+
+
+/// Breadth-first traversal marking visited vertices inside the storage. Each call starts a new epoch, so
+/// previous traversals, including aborted ones, do not affect it.
+pub fn bfs<PreOrderFunc, Edges>(edge_storage: &mut Edges, start: VHandleOf<Edges>, vertices_count: usize, mut pre_order: PreOrderFunc)
+where PreOrderFunc: FnMut(&mut Edges, VHandleOf<Edges>) -> ControlFlow, Edges: EdgeStore + TraverseMarker{
+    profile_fn!(bfs);
+    let layout = Layout::array::<VHandleOf<Edges>>(vertices_count).expect("Failed to create layout"); // Around ~50% faster than vec
+    let memory_ptr = unsafe {alloc(layout)};
+    let to_visit = unsafe {from_raw_parts_mut(memory_ptr as *mut VHandleOf<Edges>, vertices_count)};
+    edge_storage.begin_traversal();
+    edge_storage.mark_visited(start);
+    let mut end = 1;
+    to_visit[0] = start;
+    let mut i = 0;
+
+    while i != end {
+        profile_section!(bfs_loop_outer);
+        let handle = to_visit[i];
+        i += 1;
+        match pre_order(edge_storage, handle) {
+            ControlFlow::End => break,
+            ControlFlow::Continue => continue,
+            ControlFlow::Resume => {}
+        }
+
+        for offset in 0..edge_storage.len(handle) {
+            profile_section!(bfs_loop_inner);
+            let handle = edge_storage.get(handle, offset).vh();
+            if edge_storage.is_visited(handle) {
+                continue;
+            }
+            edge_storage.mark_visited(handle); // Marked when queued, so every vertex is queued at most once
+            to_visit[end] = handle;
+            end += 1;
+        }
+    }
+
+    unsafe {dealloc(memory_ptr, layout)};
+}
+/// Depth-first traversal marking visited vertices inside the storage. Each call starts a new epoch, so
+/// previous traversals, including aborted ones, do not affect it.
+pub fn dfs<PreOrderFunc, PostOrderFunc, Edges>(edge_storage: &mut Edges, start: VHandleOf<Edges>, vertices_count: usize, mut pre_order_func: PreOrderFunc,
+                                               mut post_order_func: PostOrderFunc)
+where PreOrderFunc: FnMut(&mut Edges, VHandleOf<Edges>) -> ControlFlow, PostOrderFunc: FnMut(&mut Edges, VHandleOf<Edges>), Edges: EdgeStore + TraverseMarker{
+    profile_fn!(dfs);
+    //Special case for the root:
+    edge_storage.begin_traversal();
+    edge_storage.mark_visited(start);
+    match pre_order_func(edge_storage, start){
+        ControlFlow::End | ControlFlow::Continue => return,
+        ControlFlow::Resume => {}
+    }
+
+    let layout = Layout::array::<(*const EdgeOf<Edges>, *const EdgeOf<Edges>, VHandleOf<Edges>)>(vertices_count).expect("Failed to create layout"); // Around ~50% faster than vec
+
+    // Have to use unsafe as the borrow checker doesn't know that flags and edges don't overlap
+    let memory_ptr = unsafe {alloc(layout)};
+    let to_visit = memory_ptr as *mut (*const EdgeOf<Edges>, *const EdgeOf<Edges>, VHandleOf<Edges>);
+    let mut top = 0;
+    unsafe {
+        *to_visit.offset(top) = (edge_storage.edges_ptr(start), edge_storage.edges_ptr(start).add(edge_storage.len(start)), start);
+    }
+
+    while top >= 0{
+        profile_section!(dfs_loop);
+        let (ptr, end, vertex) = unsafe{*to_visit.offset(top)};
+        if ptr == end{
+            post_order_func(edge_storage, vertex);
+            top -= 1;
+            continue;
+        }
+        unsafe {
+            *to_visit.offset(top) = (ptr.add(1), end, vertex); // Move to the next edge
+        }
+
+        let current_handle = unsafe{*ptr}.vh();
+        if edge_storage.is_visited(current_handle) {
+            continue;
+        }
+
+        edge_storage.mark_visited(current_handle);
+        match pre_order_func(edge_storage, current_handle){
+            ControlFlow::End => {
+                break;
+            },
+            ControlFlow::Continue => {
+                continue;
+            },
+            ControlFlow::Resume => {}
+        }
+
+        unsafe {
+            *to_visit.offset(top + 1) = (edge_storage.edges_ptr(current_handle), edge_storage.edges_ptr(current_handle).add(edge_storage.len(current_handle)), current_handle);
+        }
+        top += 1;
+    }
+    unsafe {dealloc(memory_ptr, layout)};
+}
+
+/// Same as `bfs` but keeps the visited state in `visited`, so it only needs shared access to the storage.
+pub fn bfs_with_visited<PreOrderFunc, Edges, Visited>(edge_storage: &Edges, start: VHandleOf<Edges>, vertices_count: usize, visited: &mut Visited, mut pre_order: PreOrderFunc)
+where PreOrderFunc: FnMut(&Edges, VHandleOf<Edges>) -> ControlFlow, Edges: EdgeStore, Visited: VisitedSet<VHandleOf<Edges>> + ?Sized{
+    profile_fn!(bfs_with_visited);
+    visited.reset(vertices_count);
+    visited.visit(start);
+    let mut to_visit = Vec::with_capacity(vertices_count);
+    to_visit.push(start);
+    let mut i = 0;
+
+    while i != to_visit.len() {
+        let handle = to_visit[i];
+        i += 1;
+        match pre_order(edge_storage, handle) {
+            ControlFlow::End => break,
+            ControlFlow::Continue => continue,
+            ControlFlow::Resume => {}
+        }
+        for next in edge_storage.edges(handle) {
+            let next = next.vh();
+            if visited.visit(next) {
+                to_visit.push(next);
+            }
+        }
+    }
+}
+
+/// Same as `dfs` but keeps the visited state in `visited`, so it only needs shared access to the storage.
+pub fn dfs_with_visited<PreOrderFunc, PostOrderFunc, Edges, Visited>(edge_storage: &Edges, start: VHandleOf<Edges>, vertices_count: usize, visited: &mut Visited,
+                                                                     mut pre_order_func: PreOrderFunc, mut post_order_func: PostOrderFunc)
+where PreOrderFunc: FnMut(&Edges, VHandleOf<Edges>) -> ControlFlow, PostOrderFunc: FnMut(&Edges, VHandleOf<Edges>), Edges: EdgeStore, Visited: VisitedSet<VHandleOf<Edges>> + ?Sized{
+    profile_fn!(dfs_with_visited);
+    visited.reset(vertices_count);
+    visited.visit(start);
+    match pre_order_func(edge_storage, start) {
+        ControlFlow::End | ControlFlow::Continue => return,
+        ControlFlow::Resume => {}
+    }
+    let mut to_visit = vec![(edge_storage.edges(start).iter(), start)];
+
+    while let Some((edges, vertex)) = to_visit.last_mut() {
+        let Some(next) = edges.next() else {
+            post_order_func(edge_storage, *vertex);
+            to_visit.pop();
+            continue;
+        };
+        let next = next.vh();
+        if !visited.visit(next) {
+            continue;
+        }
+        match pre_order_func(edge_storage, next) {
+            ControlFlow::End => return,
+            ControlFlow::Continue => continue,
+            ControlFlow::Resume => {}
+        }
+        to_visit.push((edge_storage.edges(next).iter(), next));
+    }
+}
@@ -0,0 +1,204 @@
+use std::cmp::min;
+use std::thread::available_parallelism;
+use firestorm::{profile_fn, profile_section};
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeStore, VHandleOf};
+use crate::utils::split_to_parts_mut;
+
+/// Parameters of a PageRank run. `new` gives plain PageRank, the other fields switch on the variants.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRankSettings {
+    /// Probability of following an edge instead of restarting, usually 0.85.
+    pub damping: f64,
+    /// The iteration stops once the ranks move less than this in total (L1 norm).
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    /// Split the outgoing probability in proportion to the edge weights instead of evenly.
+    /// Edges without a positive weight are never followed.
+    pub weighted: bool,
+    /// Restart distribution for personalized PageRank, one non-negative entry per vertex. Uniform when `None`.
+    pub restart: Option<Vec<f64>>,
+    /// Which slots take part, one entry per vertex. Left out vertices get no rank, their edges are ignored and
+    /// they get no share of the restart or the dangling mass. Every slot takes part when `None`, pass
+    /// `graph.is_alive` of every slot for a graph that had vertices removed.
+    pub alive: Option<Vec<bool>>,
+    /// Update the ranks on all available cores, the vertices are split like in `Vertices::async_transform`.
+    pub parallel: bool,
+}
+
+impl PageRankSettings {
+    pub fn new(damping: f64, tolerance: f64, max_iterations: usize) -> Self {
+        return PageRankSettings{
+            damping,
+            tolerance,
+            max_iterations,
+            weighted: false,
+            restart: None,
+            alive: None,
+            parallel: false,
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRank {
+    /// Rank of every vertex, the ranks sum up to 1. Vertices left out by `PageRankSettings::alive` have rank 0.
+    pub ranks: Vec<f64>,
+    pub iterations: usize,
+    /// False when `max_iterations` ran out before reaching the tolerance.
+    pub converged: bool,
+}
+
+impl PageRank {
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn rank<H: HandleIndex>(&self, vertex: H) -> f64 {
+        return self.ranks[vertex.index()];
+    }
+
+    /// Vertices from the highest rank to the lowest, ties by handle.
+    pub fn order<H: HandleIndex>(&self) -> Vec<H> {
+        let mut order: Vec<usize> = (0..self.ranks.len()).collect();
+        order.sort_by(|a, b| self.ranks[*b].total_cmp(&self.ranks[*a]).then(a.cmp(b)));
+        return order.into_iter().map(H::from_index).collect();
+    }
+}
+
+/// PageRank with even transitions and a uniform restart. Rank of vertices without outgoing edges is spread
+/// over all vertices as if they linked everywhere.
+pub fn pagerank<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize, damping: f64, tolerance: f64, max_iterations: usize) -> PageRank {
+    return pagerank_with(edge_storage, vertices_count, &PageRankSettings::new(damping, tolerance, max_iterations));
+}
+
+/// PageRank that restarts according to `restart` instead of uniformly, which ranks the vertices by their
+/// closeness to the ones with a high restart value. The rank of dangling vertices returns the same way.
+pub fn personalized_pagerank<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize, restart: &[f64], damping: f64, tolerance: f64, max_iterations: usize) -> PageRank {
+    let mut settings = PageRankSettings::new(damping, tolerance, max_iterations);
+    settings.restart = Some(restart.to_vec());
+    return pagerank_with(edge_storage, vertices_count, &settings);
+}
+
+/// Incoming transitions in CSR form, `sources[first[v]..first[v + 1]]` lead into `v` with the matching `shares`.
+struct Transitions {
+    first: Vec<usize>,
+    sources: Vec<usize>,
+    shares: Vec<f64>,
+    dangling: Vec<usize>,
+}
+
+impl Transitions {
+    fn new<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize, weighted: bool, alive: &[bool]) -> Self {
+        let share_of = |weight: i64| if !weighted { 1.0 } else { weight.max(0) as f64 };
+        let mut totals = vec![0.0; vertices_count];
+        let mut in_degrees = vec![0usize; vertices_count];
+        for vertex in (0..vertices_count).filter(|vertex| alive[*vertex]) {
+            for edge in edge_storage.edges(VHandleOf::<Edges>::from_index(vertex)).iter().filter(|edge| alive[edge.vh().index()]) {
+                let share = share_of(edge.wgt().into());
+                if share > 0.0 {
+                    totals[vertex] += share;
+                    in_degrees[edge.vh().index()] += 1;
+                }
+            }
+        }
+
+        let mut first = Vec::with_capacity(vertices_count + 1);
+        first.push(0);
+        for vertex in 0..vertices_count {
+            first.push(first[vertex] + in_degrees[vertex]);
+        }
+        let mut fill = first.clone();
+        let mut sources = vec![0usize; first[vertices_count]];
+        let mut shares = vec![0.0; first[vertices_count]];
+        for vertex in (0..vertices_count).filter(|vertex| alive[*vertex]) {
+            for edge in edge_storage.edges(VHandleOf::<Edges>::from_index(vertex)).iter().filter(|edge| alive[edge.vh().index()]) {
+                let share = share_of(edge.wgt().into());
+                if share > 0.0 {
+                    let target = edge.vh().index();
+                    sources[fill[target]] = vertex;
+                    shares[fill[target]] = share / totals[vertex];
+                    fill[target] += 1;
+                }
+            }
+        }
+        let dangling = (0..vertices_count).filter(|vertex| alive[*vertex] && totals[*vertex] == 0.0).collect();
+        return Transitions{first, sources, shares, dangling};
+    }
+
+    /// Writes the next ranks of the vertices starting at `offset` into `next` and returns how far they moved.
+    fn step(&self, ranks: &[f64], restart: &[f64], damping: f64, dangling_mass: f64, offset: usize, next: &mut [f64]) -> f64 {
+        let mut change = 0.0;
+        for (index, rank) in next.iter_mut().enumerate() {
+            let vertex = offset + index;
+            let mut incoming = dangling_mass * restart[vertex];
+            for edge in self.first[vertex]..self.first[vertex + 1] {
+                incoming += self.shares[edge] * ranks[self.sources[edge]];
+            }
+            *rank = (1.0 - damping) * restart[vertex] + damping * incoming;
+            change += (*rank - ranks[vertex]).abs();
+        }
+        return change;
+    }
+}
+
+/// Power iteration of PageRank as configured by `settings`. Panics when `damping` is outside of [0, 1], the
+/// liveness vector has the wrong length or the restart vector has the wrong length, a negative entry or sums up
+/// to zero over the live vertices.
+pub fn pagerank_with<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize, settings: &PageRankSettings) -> PageRank {
+    profile_fn!(pagerank_with);
+    assert!((0.0..=1.0).contains(&settings.damping), "Damping must be between 0 and 1: {}", settings.damping);
+    let alive = match &settings.alive {
+        Some(alive) => {
+            assert_eq!(alive.len(), vertices_count, "Liveness vector needs one entry per vertex");
+            alive.clone()
+        }
+        None => vec![true; vertices_count],
+    };
+    let live_count = alive.iter().filter(|alive| **alive).count();
+    if live_count == 0 {
+        return PageRank{ranks: vec![0.0; vertices_count], iterations: 0, converged: true};
+    }
+    let restart: Vec<f64> = match &settings.restart {
+        Some(restart) => {
+            assert_eq!(restart.len(), vertices_count, "Restart vector needs one entry per vertex");
+            assert!(restart.iter().all(|value| *value >= 0.0), "Restart vector must not be negative");
+            let total: f64 = restart.iter().zip(&alive).filter(|(_, alive)| **alive).map(|(value, _)| value).sum();
+            assert!(total > 0.0, "Restart vector must not be all zero");
+            restart.iter().zip(&alive).map(|(value, alive)| if *alive { value / total } else { 0.0 }).collect()
+        }
+        None => alive.iter().map(|alive| if *alive { 1.0 / live_count as f64 } else { 0.0 }).collect(),
+    };
+    let transitions = Transitions::new(edge_storage, vertices_count, settings.weighted, &alive);
+    let parallelism_count = match settings.parallel {
+        true => min(available_parallelism().map(|count| count.get()).unwrap_or(1), vertices_count),
+        false => 1,
+    };
+
+    let mut ranks = restart.clone();
+    let mut next = vec![0.0; vertices_count];
+    for iteration in 1..=settings.max_iterations {
+        profile_section!(power_step);
+        let dangling_mass: f64 = transitions.dangling.iter().map(|vertex| ranks[*vertex]).sum();
+        let change = if parallelism_count == 1 {
+            transitions.step(&ranks, &restart, settings.damping, dangling_mass, 0, &mut next)
+        } else {
+            let parts = split_to_parts_mut(&mut next, parallelism_count);
+            let (transitions, ranks, restart) = (&transitions, &ranks, &restart);
+            std::thread::scope(|scope| {
+                let mut offset = 0;
+                let mut workers = Vec::with_capacity(parts.len());
+                for part in parts {
+                    let part_offset = offset;
+                    offset += part.len();
+                    workers.push(scope.spawn(move || {
+                        return transitions.step(ranks, restart, settings.damping, dangling_mass, part_offset, part);
+                    }));
+                }
+                return workers.into_iter().map(|worker| worker.join().unwrap()).sum();
+            })
+        };
+        std::mem::swap(&mut ranks, &mut next);
+        if change < settings.tolerance {
+            return PageRank{ranks, iterations: iteration, converged: true};
+        }
+    }
+    return PageRank{ranks, iterations: settings.max_iterations, converged: false};
+}
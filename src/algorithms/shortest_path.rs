@@ -0,0 +1,279 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use firestorm::profile_fn;
+use crate::algorithms::ControlFlow;
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeStore, VHandleOf};
+
+/// Path costs are summed in `i64` regardless of the weight width of the storage.
+pub type Cost = i64;
+
+/// A path from its first to its last vertex together with the summed edge weights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<H> {
+    pub vertices: Vec<H>,
+    pub cost: Cost,
+}
+
+/// Single source shortest path tree. `distances[v]` and `predecessors[v]` are `None` for unreachable vertices,
+/// the source has a distance of 0 and no predecessor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths<H> {
+    pub source: H,
+    pub distances: Vec<Option<Cost>>,
+    pub predecessors: Vec<Option<H>>,
+}
+
+impl<H: HandleIndex> ShortestPaths<H> {
+    pub(in crate) fn new(source: H, vertices_count: usize) -> Self {
+        let mut paths = ShortestPaths{
+            source,
+            distances: vec![None; vertices_count],
+            predecessors: vec![None; vertices_count],
+        };
+        paths.distances[source.index()] = Some(0);
+        return paths;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn distance(&self, vertex: H) -> Option<Cost> {
+        return self.distances[vertex.index()];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn predecessor(&self, vertex: H) -> Option<H> {
+        return self.predecessors[vertex.index()];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn is_reachable(&self, vertex: H) -> bool {
+        return self.distances[vertex.index()].is_some();
+    }
+
+    /// Walks the predecessors back from `target`. `None` if `target` is unreachable.
+    pub fn path_to(&self, target: H) -> Option<Path<H>> {
+        let cost = self.distance(target)?;
+        return Some(Path{
+            vertices: reconstruct_path(&self.predecessors, target),
+            cost,
+        });
+    }
+}
+
+/// Follows `predecessors` from `target` until a vertex without predecessor and returns the vertices in path order.
+pub(in crate) fn reconstruct_path<H: HandleIndex>(predecessors: &[Option<H>], target: H) -> Vec<H> {
+    let mut path = vec![target];
+    let mut current = target;
+    while let Some(previous) = predecessors[current.index()] {
+        path.push(previous);
+        current = previous;
+    }
+    path.reverse();
+    return path;
+}
+
+/// Shortest paths from `source` to every vertex. Edge weights must not be negative, use `bellman_ford` otherwise.
+/// A negative weight is only caught by a debug assertion, in release builds the result is unspecified.
+pub fn dijkstra<Edges: EdgeStore>(edge_storage: &Edges, source: VHandleOf<Edges>, vertices_count: usize) -> ShortestPaths<VHandleOf<Edges>> {
+    profile_fn!(dijkstra);
+    return dijkstra_with(edge_storage, source, vertices_count, None, |_src, _target, weight| weight);
+}
+
+/// Shortest path from `source` to `target`. The search stops as soon as `target` is settled.
+/// Same requirement on the weights as `dijkstra`.
+pub fn dijkstra_to<Edges: EdgeStore>(edge_storage: &Edges, source: VHandleOf<Edges>, target: VHandleOf<Edges>, vertices_count: usize) -> Option<Path<VHandleOf<Edges>>> {
+    profile_fn!(dijkstra_to);
+    return dijkstra_with(edge_storage, source, vertices_count, Some(target), |_src, _target, weight| weight).path_to(target);
+}
+
+/// Dijkstra with the edge cost computed by `cost(src, target, weight)`, used by Johnson for reweighting.
+pub(in crate) fn dijkstra_with<Edges, CostFunc>(edge_storage: &Edges, source: VHandleOf<Edges>, vertices_count: usize, target: Option<VHandleOf<Edges>>,
+                                                 mut cost: CostFunc) -> ShortestPaths<VHandleOf<Edges>>
+where Edges: EdgeStore, CostFunc: FnMut(VHandleOf<Edges>, VHandleOf<Edges>, Cost) -> Cost {
+    let mut paths = ShortestPaths::new(source, vertices_count);
+    let mut settled = vec![false; vertices_count];
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((0, source)));
+
+    while let Some(Reverse((distance, vertex))) = queue.pop() {
+        if settled[vertex.index()] {
+            continue;
+        }
+        settled[vertex.index()] = true;
+        if Some(vertex) == target {
+            break;
+        }
+
+        for edge in edge_storage.edges(vertex) {
+            let next = edge.vh();
+            let weight = cost(vertex, next, edge.wgt().into());
+            debug_assert!(weight >= 0, "Dijkstra requires non negative weights, got {} on {} -> {}", weight, vertex, next);
+            let next_distance = distance + weight;
+            if paths.distances[next.index()].is_none_or(|current| next_distance < current) {
+                paths.distances[next.index()] = Some(next_distance);
+                paths.predecessors[next.index()] = Some(vertex);
+                queue.push(Reverse((next_distance, next)));
+            }
+        }
+    }
+    return paths;
+}
+
+/// A cycle with a negative total weight, reachable from the source. The vertices are listed in edge order
+/// starting with the smallest handle, the last vertex connects back to the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle<H> {
+    pub vertices: Vec<H>,
+}
+
+/// Shortest paths from `source` allowing negative weights. Uses the queue based variant (SPFA), only vertices
+/// whose distance changed are relaxed again. Fails with the cycle if a negative cycle is reachable from `source`.
+pub fn bellman_ford<Edges: EdgeStore>(edge_storage: &Edges, source: VHandleOf<Edges>, vertices_count: usize) -> Result<ShortestPaths<VHandleOf<Edges>>, NegativeCycle<VHandleOf<Edges>>> {
+    profile_fn!(bellman_ford);
+    let mut paths = ShortestPaths::new(source, vertices_count);
+    relax_from(edge_storage, &mut paths.distances, &mut paths.predecessors, &[source])?;
+    return Ok(paths);
+}
+
+/// Relaxes edges starting from the already reachable `sources` until no distance changes.
+/// Used with every vertex as a source at distance 0 to emulate a virtual source for Johnson.
+pub(in crate) fn relax_from<Edges: EdgeStore>(edge_storage: &Edges, distances: &mut [Option<Cost>], predecessors: &mut [Option<VHandleOf<Edges>>],
+                                               sources: &[VHandleOf<Edges>]) -> Result<(), NegativeCycle<VHandleOf<Edges>>> {
+    let vertices_count = distances.len();
+    let mut queued = vec![false; vertices_count];
+    let mut path_lengths = vec![0usize; vertices_count];
+    let mut queue = VecDeque::with_capacity(sources.len());
+    for &source in sources {
+        queue.push_back(source);
+        queued[source.index()] = true;
+    }
+
+    while let Some(vertex) = queue.pop_front() {
+        queued[vertex.index()] = false;
+        let distance = distances[vertex.index()].expect("Queued vertices are reachable");
+
+        for edge in edge_storage.edges(vertex) {
+            let next = edge.vh();
+            let next_distance = distance + edge.wgt().into();
+            if distances[next.index()].is_some_and(|current| next_distance >= current) {
+                continue;
+            }
+            distances[next.index()] = Some(next_distance);
+            predecessors[next.index()] = Some(vertex);
+            path_lengths[next.index()] = path_lengths[vertex.index()] + 1;
+
+            // A shortest path has at most vertices_count - 1 edges, a longer one has to go through a negative cycle
+            if path_lengths[next.index()] >= vertices_count {
+                if let Some(cycle) = predecessor_cycle(predecessors) {
+                    return Err(cycle);
+                }
+            }
+            if !queued[next.index()] {
+                queued[next.index()] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    return Ok(());
+}
+
+/// Finds a cycle in the predecessor graph. While relaxing, any such cycle has a negative weight.
+pub(in crate) fn predecessor_cycle<H: HandleIndex>(predecessors: &[Option<H>]) -> Option<NegativeCycle<H>> {
+    const UNSEEN: usize = usize::MAX;
+    let mut walk_of = vec![UNSEEN; predecessors.len()];
+
+    for start in 0..predecessors.len() {
+        let mut current = start;
+        while walk_of[current] == UNSEEN {
+            walk_of[current] = start;
+            match predecessors[current] {
+                Some(previous) => current = previous.index(),
+                None => break,
+            }
+        }
+        if walk_of[current] != start || predecessors[current].is_none() {
+            continue; // Joined an older walk or ended at the source
+        }
+
+        // `current` was reached twice during this walk, so it lies on a cycle
+        let mut vertices = vec![H::from_index(current)];
+        let mut vertex = predecessors[current].unwrap();
+        while vertex.index() != current {
+            vertices.push(vertex);
+            vertex = predecessors[vertex.index()].unwrap();
+        }
+        vertices.reverse();
+        let smallest = (0..vertices.len()).min_by_key(|&i| vertices[i]).unwrap();
+        vertices.rotate_left(smallest);
+        return Some(NegativeCycle{vertices});
+    }
+    return None;
+}
+
+/// Outcome of a goal directed search that can be ended early by the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchOutcome<H> {
+    Found(Path<H>),
+    /// Every vertex reachable from the start was expanded without reaching the goal.
+    Unreachable,
+    /// The visit callback returned `ControlFlow::End`.
+    Aborted,
+}
+
+impl<H> SearchOutcome<H> {
+    pub fn path(self) -> Option<Path<H>> {
+        return match self {
+            SearchOutcome::Found(path) => Some(path),
+            _ => None,
+        };
+    }
+}
+
+/// A* from `start` to `goal`. `heuristic(v)` estimates the remaining cost from `v` to `goal` and must not
+/// overestimate it, otherwise the returned path is not guaranteed to be the shortest one.
+/// Edge weights must not be negative, the result is unspecified otherwise.
+pub fn astar<Edges, Heuristic>(edge_storage: &Edges, start: VHandleOf<Edges>, goal: VHandleOf<Edges>, vertices_count: usize, heuristic: Heuristic) -> Option<Path<VHandleOf<Edges>>>
+where Edges: EdgeStore, Heuristic: FnMut(VHandleOf<Edges>) -> Cost {
+    profile_fn!(astar);
+    return astar_with(edge_storage, start, goal, vertices_count, heuristic, |_vertex| ControlFlow::Resume).path();
+}
+
+/// A* calling `visit` before a vertex is expanded. `Continue` skips the edges of the vertex and `End` aborts
+/// the search, e.g. once a node budget is spent.
+pub fn astar_with<Edges, Heuristic, VisitFunc>(edge_storage: &Edges, start: VHandleOf<Edges>, goal: VHandleOf<Edges>, vertices_count: usize,
+                                              mut heuristic: Heuristic, mut visit: VisitFunc) -> SearchOutcome<VHandleOf<Edges>>
+where Edges: EdgeStore, Heuristic: FnMut(VHandleOf<Edges>) -> Cost, VisitFunc: FnMut(VHandleOf<Edges>) -> ControlFlow {
+    profile_fn!(astar_with);
+    let mut paths = ShortestPaths::new(start, vertices_count);
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_estimate, distance, vertex))) = queue.pop() {
+        if paths.distances[vertex.index()].is_some_and(|best| distance > best) {
+            continue; // A shorter way to the vertex was queued after this entry
+        }
+        if vertex == goal {
+            return SearchOutcome::Found(Path{
+                vertices: reconstruct_path(&paths.predecessors, goal),
+                cost: distance,
+            });
+        }
+        match visit(vertex) {
+            ControlFlow::End => return SearchOutcome::Aborted,
+            ControlFlow::Continue => continue,
+            ControlFlow::Resume => {}
+        }
+
+        for edge in edge_storage.edges(vertex) {
+            let next = edge.vh();
+            let next_distance = distance + edge.wgt().into();
+            if paths.distances[next.index()].is_some_and(|current| next_distance >= current) {
+                continue;
+            }
+            paths.distances[next.index()] = Some(next_distance);
+            paths.predecessors[next.index()] = Some(vertex);
+            queue.push(Reverse((next_distance + heuristic(next), next_distance, next)));
+        }
+    }
+    return SearchOutcome::Unreachable;
+}
@@ -0,0 +1,163 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use firestorm::profile_fn;
+use crate::algorithms::shortest_path::Cost;
+use crate::algorithms::union_find::UnionFind;
+use crate::edge_storage::EdgeStorage;
+use crate::graph::Graph;
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeOf, EdgeOperator, EdgeStore, VHandleOf};
+
+/// Minimum spanning forest, one tree per connected component. `edges` holds `(src, target, weight)`.
+/// `vertices_count` is the number of slots the forest was computed over, `tree_count` only counts live vertices.
+/// `covered[v]` is `false` for the slots the `_with` variants left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanningForest<P: EdgePacking> {
+    pub edges: Vec<(P::VHandle, P::VHandle, P::Weight)>,
+    pub total_weight: Cost,
+    pub tree_count: usize,
+    pub vertices_count: usize,
+    pub covered: Vec<bool>,
+}
+
+/// A candidate link of Prim ordered by weight, with the tree vertex and edge it leaves through.
+type PrimCandidate<Edges> = Reverse<(Cost, VHandleOf<Edges>, Option<(VHandleOf<Edges>, EdgeOf<Edges>)>)>;
+
+impl<P: EdgePacking> SpanningForest<P> {
+    /// Whether the forest is a single tree spanning every vertex.
+    pub fn is_spanning_tree(&self) -> bool {
+        return self.tree_count == 1;
+    }
+
+    /// Builds a graph laid out for `TreeView`, vertex `v` of the result is vertex `v` of the source graph.
+    /// The tree containing `root` hangs from `root`, every other tree from its smallest vertex.
+    /// Slots the forest does not cover stay removed in the result and `value` is not called for them.
+    pub fn to_tree_graph<V, ValueFunc>(&self, root: P::VHandle, mut value: ValueFunc) -> Graph<V, EdgeStorage<P>>
+    where ValueFunc: FnMut(P::VHandle) -> V {
+        profile_fn!(to_tree_graph);
+        let mut neighbors = vec![Vec::new(); self.vertices_count];
+        for (src, target, _weight) in &self.edges {
+            neighbors[src.index()].push(*target);
+            neighbors[target.index()].push(*src);
+        }
+
+        let mut graph = Graph::with_storage(EdgeStorage::<P>::new());
+        let mut tree_view = graph.tree_view();
+        for vertex in 0..self.vertices_count {
+            if !self.covered[vertex] {
+                tree_view.values.push_removed();
+                tree_view.nodes.extend_edge_storage(0);
+                continue;
+            }
+            tree_view.create_node(value(P::VHandle::from_index(vertex)));
+        }
+
+        let mut placed = vec![false; self.vertices_count];
+        let mut to_visit = Vec::with_capacity(self.vertices_count);
+        let roots = std::iter::once(root.index()).chain(0..self.vertices_count);
+        for tree_root in roots {
+            if placed[tree_root] || !self.covered[tree_root] {
+                continue;
+            }
+            placed[tree_root] = true;
+            to_visit.clear();
+            to_visit.push(P::VHandle::from_index(tree_root));
+            let mut i = 0;
+            while i != to_visit.len() {
+                let parent = to_visit[i];
+                i += 1;
+                for child in &neighbors[parent.index()] {
+                    if placed[child.index()] {
+                        continue;
+                    }
+                    placed[child.index()] = true;
+                    tree_view.add_child(parent, *child);
+                    to_visit.push(*child);
+                }
+            }
+        }
+        return graph;
+    }
+}
+
+/// Kruskal's minimum spanning forest. Edge directions are ignored, so both a symmetric storage as kept by
+/// `UndirectedGraph` and one holding each edge once work. Self loops are skipped.
+/// Slots of removed vertices count as trees of their own, see `kruskal_with`.
+pub fn kruskal<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> SpanningForest<EdgeOf<Edges>> {
+    return kruskal_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Same as `kruskal`, but only vertices accepted by `is_alive` are spanned.
+/// Pass `|vertex| graph.is_alive(vertex)` for a graph that had vertices removed.
+pub fn kruskal_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> SpanningForest<EdgeOf<Edges>>
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(kruskal);
+    let mut candidates = Vec::new();
+    let covered: Vec<bool> = (0..vertices_count).map(|vertex| is_alive(VHandleOf::<Edges>::from_index(vertex))).collect();
+    let live_count = covered.iter().filter(|covered| **covered).count();
+    for vertex in 0..vertices_count {
+        let vertex = VHandleOf::<Edges>::from_index(vertex);
+        if !covered[vertex.index()] {
+            continue;
+        }
+        for edge in edge_storage.edges(vertex) {
+            if edge.vh() != vertex && is_alive(edge.vh()) {
+                candidates.push((edge.wgt().into(), vertex, *edge));
+            }
+        }
+    }
+    candidates.sort_by_key(|(weight, _src, _edge)| *weight);
+
+    let mut forest = SpanningForest{edges: Vec::new(), total_weight: 0, tree_count: live_count, vertices_count, covered};
+    let mut sets = UnionFind::<VHandleOf<Edges>>::new(vertices_count);
+    for (weight, src, edge) in candidates {
+        if sets.union(src, edge.vh()) {
+            forest.edges.push((src, edge.vh(), edge.wgt()));
+            forest.total_weight += weight;
+            forest.tree_count -= 1;
+        }
+    }
+    return forest;
+}
+
+/// Prim's minimum spanning forest, growing one tree at a time from the smallest vertex not covered yet.
+/// Only outgoing edges are followed, so the storage has to hold both directions as `UndirectedGraph` does.
+/// Slots of removed vertices count as trees of their own, see `prim_with`.
+pub fn prim<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> SpanningForest<EdgeOf<Edges>> {
+    return prim_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Same as `prim`, but only vertices accepted by `is_alive` are spanned.
+pub fn prim_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> SpanningForest<EdgeOf<Edges>>
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(prim);
+    let covered = (0..vertices_count).map(|vertex| is_alive(VHandleOf::<Edges>::from_index(vertex))).collect();
+    let mut forest = SpanningForest{edges: Vec::new(), total_weight: 0, tree_count: 0, vertices_count, covered};
+    let mut in_tree = vec![false; vertices_count];
+    let mut queue: BinaryHeap<PrimCandidate<Edges>> = BinaryHeap::new();
+
+    for root in 0..vertices_count {
+        if in_tree[root] || !forest.covered[root] {
+            continue;
+        }
+        forest.tree_count += 1;
+        queue.push(Reverse((0, VHandleOf::<Edges>::from_index(root), None)));
+
+        while let Some(Reverse((weight, vertex, link))) = queue.pop() {
+            if in_tree[vertex.index()] {
+                continue;
+            }
+            in_tree[vertex.index()] = true;
+            if let Some((src, edge)) = link {
+                forest.edges.push((src, edge.vh(), edge.wgt()));
+                forest.total_weight += weight;
+            }
+            for edge in edge_storage.edges(vertex) {
+                if !in_tree[edge.vh().index()] && forest.covered[edge.vh().index()] {
+                    queue.push(Reverse((edge.wgt().into(), edge.vh(), Some((vertex, *edge)))));
+                }
+            }
+        }
+    }
+    return forest;
+}
@@ -0,0 +1,187 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::{Display, Formatter};
+use firestorm::profile_fn;
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeStore, VHandleOf};
+
+/// The graph is not acyclic. The vertices are listed in edge order, the last one connects back to the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<H> {
+    pub vertices: Vec<H>,
+}
+
+impl<H: HandleIndex> Display for Cycle<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cycle ")?;
+        for vertex in &self.vertices {
+            write!(f, "{} -> ", vertex)?;
+        }
+        return write!(f, "{}", self.vertices[0]);
+    }
+}
+
+impl<H: HandleIndex> std::error::Error for Cycle<H> {}
+
+/// Kahn's algorithm. Vertices become ready once all of their predecessors are ordered and are taken in the
+/// order they became ready.
+/// Slots of removed vertices are ordered like any other vertex, see `topological_sort_with`.
+pub fn topological_sort<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Result<Vec<VHandleOf<Edges>>, Cycle<VHandleOf<Edges>>> {
+    return topological_sort_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Same as `topological_sort`, but only vertices accepted by `is_alive` are ordered.
+/// Pass `|vertex| graph.is_alive(vertex)` for a graph that had vertices removed.
+pub fn topological_sort_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> Result<Vec<VHandleOf<Edges>>, Cycle<VHandleOf<Edges>>>
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(topological_sort);
+    let mut in_degrees = in_degrees(edge_storage, vertices_count, &is_alive);
+    let mut ready: VecDeque<VHandleOf<Edges>> = (0..vertices_count)
+        .map(VHandleOf::<Edges>::from_index)
+        .filter(|&vertex| is_alive(vertex) && in_degrees[vertex.index()] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(vertices_count);
+
+    while let Some(vertex) = ready.pop_front() {
+        order.push(vertex);
+        for next in edge_storage.edges(vertex).iter().map(|edge| edge.vh()).filter(|next| is_alive(*next)) {
+            in_degrees[next.index()] -= 1;
+            if in_degrees[next.index()] == 0 {
+                ready.push_back(next);
+            }
+        }
+    }
+    if order.len() != live_count(vertices_count, &is_alive) {
+        return Err(find_cycle_with(edge_storage, vertices_count, is_alive).expect("Unordered vertices lie on or behind a cycle"));
+    }
+    return Ok(order);
+}
+
+/// Kahn's algorithm taking the ready vertex with the smallest `priority` first. With the handle itself as
+/// priority this is the lexicographically smallest order.
+pub fn topological_sort_by_key<Edges, Key, PriorityFunc>(edge_storage: &Edges, vertices_count: usize, priority: PriorityFunc) -> Result<Vec<VHandleOf<Edges>>, Cycle<VHandleOf<Edges>>>
+where Edges: EdgeStore, Key: Ord, PriorityFunc: FnMut(VHandleOf<Edges>) -> Key {
+    return topological_sort_by_key_with(edge_storage, vertices_count, priority, |_vertex| true);
+}
+
+/// Same as `topological_sort_by_key`, but only vertices accepted by `is_alive` are ordered.
+pub fn topological_sort_by_key_with<Edges, Key, PriorityFunc, Alive>(edge_storage: &Edges, vertices_count: usize, mut priority: PriorityFunc,
+                                                                    is_alive: Alive) -> Result<Vec<VHandleOf<Edges>>, Cycle<VHandleOf<Edges>>>
+where Edges: EdgeStore, Key: Ord, PriorityFunc: FnMut(VHandleOf<Edges>) -> Key, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(topological_sort_by_key);
+    let mut in_degrees = in_degrees(edge_storage, vertices_count, &is_alive);
+    let mut ready = BinaryHeap::new();
+    for vertex in (0..vertices_count).map(VHandleOf::<Edges>::from_index).filter(|&vertex| is_alive(vertex) && in_degrees[vertex.index()] == 0) {
+        ready.push(Reverse((priority(vertex), vertex)));
+    }
+    let mut order = Vec::with_capacity(vertices_count);
+
+    while let Some(Reverse((_key, vertex))) = ready.pop() {
+        order.push(vertex);
+        for next in edge_storage.edges(vertex).iter().map(|edge| edge.vh()).filter(|next| is_alive(*next)) {
+            in_degrees[next.index()] -= 1;
+            if in_degrees[next.index()] == 0 {
+                ready.push(Reverse((priority(next), next)));
+            }
+        }
+    }
+    if order.len() != live_count(vertices_count, &is_alive) {
+        return Err(find_cycle_with(edge_storage, vertices_count, is_alive).expect("Unordered vertices lie on or behind a cycle"));
+    }
+    return Ok(order);
+}
+
+/// Depth first topological sort, the reverse of the finishing order. Uses an explicit stack like `dfs`.
+pub fn topological_sort_dfs<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Result<Vec<VHandleOf<Edges>>, Cycle<VHandleOf<Edges>>> {
+    return topological_sort_dfs_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Same as `topological_sort_dfs`, but only vertices accepted by `is_alive` are ordered.
+pub fn topological_sort_dfs_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> Result<Vec<VHandleOf<Edges>>, Cycle<VHandleOf<Edges>>>
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    profile_fn!(topological_sort_dfs);
+    let mut finished = Vec::with_capacity(vertices_count);
+    if let Some(cycle) = dfs_finish_order(edge_storage, vertices_count, &is_alive, &mut finished) {
+        return Err(cycle);
+    }
+    finished.reverse();
+    return Ok(finished);
+}
+
+/// Any cycle of the graph, `None` if it is acyclic.
+pub fn find_cycle<Edges: EdgeStore>(edge_storage: &Edges, vertices_count: usize) -> Option<Cycle<VHandleOf<Edges>>> {
+    return find_cycle_with(edge_storage, vertices_count, |_vertex| true);
+}
+
+/// Any cycle through vertices accepted by `is_alive`, `None` if there is none.
+pub fn find_cycle_with<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: Alive) -> Option<Cycle<VHandleOf<Edges>>>
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    return dfs_finish_order(edge_storage, vertices_count, &is_alive, &mut Vec::new());
+}
+
+/// In-degrees counting only the edges between live vertices.
+fn in_degrees<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: &Alive) -> Vec<usize>
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    let mut in_degrees = vec![0usize; vertices_count];
+    for vertex in (0..vertices_count).map(VHandleOf::<Edges>::from_index).filter(|vertex| is_alive(*vertex)) {
+        for next in edge_storage.edges(vertex).iter().map(|edge| edge.vh()).filter(|next| is_alive(*next)) {
+            in_degrees[next.index()] += 1;
+        }
+    }
+    return in_degrees;
+}
+
+fn live_count<H: HandleIndex, Alive: Fn(H) -> bool>(vertices_count: usize, is_alive: &Alive) -> usize {
+    return (0..vertices_count).filter(|vertex| is_alive(H::from_index(*vertex))).count();
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    New,
+    OnStack,
+    Finished,
+}
+
+/// Pushes the vertices to `finished` in finishing order. Stops at the first back edge and returns the cycle
+/// it closes, which is the part of the stack from the target of the back edge to the top.
+fn dfs_finish_order<Edges, Alive>(edge_storage: &Edges, vertices_count: usize, is_alive: &Alive, finished: &mut Vec<VHandleOf<Edges>>) -> Option<Cycle<VHandleOf<Edges>>>
+where Edges: EdgeStore, Alive: Fn(VHandleOf<Edges>) -> bool {
+    let mut marks = vec![Mark::New; vertices_count];
+    let mut stack: Vec<(VHandleOf<Edges>, usize)> = Vec::new();
+
+    for root in 0..vertices_count {
+        if marks[root] != Mark::New || !is_alive(VHandleOf::<Edges>::from_index(root)) {
+            continue;
+        }
+        marks[root] = Mark::OnStack;
+        stack.push((VHandleOf::<Edges>::from_index(root), 0));
+
+        while let Some((vertex, offset)) = stack.last_mut() {
+            let vertex = *vertex;
+            let edges = edge_storage.edges(vertex);
+            if *offset == edges.len() {
+                marks[vertex.index()] = Mark::Finished;
+                finished.push(vertex);
+                stack.pop();
+                continue;
+            }
+            let next = edges[*offset].vh();
+            *offset += 1;
+            if !is_alive(next) {
+                continue;
+            }
+            match marks[next.index()] {
+                Mark::Finished => {}
+                Mark::New => {
+                    marks[next.index()] = Mark::OnStack;
+                    stack.push((next, 0));
+                }
+                Mark::OnStack => {
+                    let start = stack.iter().position(|(on_stack, _)| *on_stack == next).unwrap();
+                    return Some(Cycle{vertices: stack[start..].iter().map(|(on_stack, _)| *on_stack).collect()});
+                }
+            }
+        }
+    }
+    return None;
+}
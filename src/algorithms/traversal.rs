@@ -0,0 +1,182 @@
+use firestorm::profile_method;
+use crate::handles::EdgePacking;
+use crate::algorithms::visited::VisitedBits;
+use crate::traits::{EdgeStore, VHandleOf, VisitedSet};
+
+/// A vertex reached by a traversal. `parent` is `None` for the start vertex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit<H> {
+    pub vertex: H,
+    pub depth: usize,
+    pub parent: Option<H>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfsEvent<H> {
+    /// The vertex was discovered, its children were not visited yet.
+    Pre(Visit<H>),
+    /// All children of the vertex were visited.
+    Post(Visit<H>),
+}
+
+/// Lazy breadth-first traversal. The children of a vertex are queued when the next vertex is requested,
+/// so `skip_children` called right after a vertex was yielded prunes its subtree.
+/// The visited state is owned by the traversal (or borrowed via `&mut`), the storage is only read.
+pub struct Bfs<'a, Edges: EdgeStore, Visited = VisitedBits> {
+    edge_storage: &'a Edges,
+    visited: Visited,
+    to_visit: Vec<Visit<VHandleOf<Edges>>>,
+    next: usize,
+    pending: Option<Visit<VHandleOf<Edges>>>,
+}
+
+impl<'a, Edges: EdgeStore> Bfs<'a, Edges> {
+    pub fn new(edge_storage: &'a Edges, start: VHandleOf<Edges>, vertices_count: usize) -> Self {
+        return Bfs::with_visited(edge_storage, start, vertices_count, VisitedBits::default());
+    }
+}
+
+impl<'a, Edges: EdgeStore, Visited: VisitedSet<VHandleOf<Edges>>> Bfs<'a, Edges, Visited> {
+    /// Traverses with a caller provided visited set. The set is reset before use.
+    pub fn with_visited(edge_storage: &'a Edges, start: VHandleOf<Edges>, vertices_count: usize, mut visited: Visited) -> Self {
+        visited.reset(vertices_count);
+        visited.visit(start);
+        let mut to_visit = Vec::with_capacity(vertices_count);
+        to_visit.push(Visit{vertex: start, depth: 0, parent: None});
+        return Bfs{
+            edge_storage,
+            visited,
+            to_visit,
+            next: 0,
+            pending: None,
+        }
+    }
+
+    /// Do not descend into the vertex yielded last.
+    pub fn skip_children(&mut self) {
+        self.pending = None;
+    }
+
+    fn expand(&mut self, visit: Visit<VHandleOf<Edges>>) {
+        for edge in self.edge_storage.edges(visit.vertex) {
+            let target = edge.vh();
+            if !self.visited.visit(target) {
+                continue;
+            }
+            self.to_visit.push(Visit{vertex: target, depth: visit.depth + 1, parent: Some(visit.vertex)});
+        }
+    }
+}
+
+impl<'a, Edges: EdgeStore, Visited: VisitedSet<VHandleOf<Edges>>> Iterator for Bfs<'a, Edges, Visited> {
+    type Item = Visit<VHandleOf<Edges>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        profile_method!(next);
+        if let Some(visit) = self.pending.take() {
+            self.expand(visit);
+        }
+        let visit = *self.to_visit.get(self.next)?;
+        self.next += 1;
+        self.pending = Some(visit);
+        return Some(visit);
+    }
+}
+
+struct DfsFrame<H> {
+    visit: Visit<H>,
+    edge: usize,
+}
+
+/// Lazy depth-first traversal yielding a `Pre` event when a vertex is discovered and a `Post` event once all
+/// of its children are done. Edges are followed in storage order.
+pub struct Dfs<'a, Edges: EdgeStore, Visited = VisitedBits> {
+    edge_storage: &'a Edges,
+    visited: Visited,
+    stack: Vec<DfsFrame<VHandleOf<Edges>>>,
+    start: Option<VHandleOf<Edges>>,
+    // Whether the last event was a `Pre`, in which case its vertex is on top of the stack
+    after_pre: bool,
+}
+
+impl<'a, Edges: EdgeStore> Dfs<'a, Edges> {
+    pub fn new(edge_storage: &'a Edges, start: VHandleOf<Edges>, vertices_count: usize) -> Self {
+        return Dfs::with_visited(edge_storage, start, vertices_count, VisitedBits::default());
+    }
+}
+
+impl<'a, Edges: EdgeStore, Visited: VisitedSet<VHandleOf<Edges>>> Dfs<'a, Edges, Visited> {
+    /// Traverses with a caller provided visited set. The set is reset before use.
+    pub fn with_visited(edge_storage: &'a Edges, start: VHandleOf<Edges>, vertices_count: usize, mut visited: Visited) -> Self {
+        visited.reset(vertices_count);
+        return Dfs{
+            edge_storage,
+            visited,
+            stack: Vec::new(),
+            start: Some(start),
+            after_pre: false,
+        }
+    }
+
+    /// Do not descend into the vertex of the last `Pre` event. Its `Post` event is still yielded.
+    /// Does nothing if the last event was a `Post`, the vertex is finished by then.
+    pub fn skip_children(&mut self) {
+        if !self.after_pre {
+            return;
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.edge = usize::MAX;
+        }
+    }
+
+    /// Only the `Pre` events, i.e. the vertices in discovery order.
+    pub fn pre_order(self) -> impl Iterator<Item = Visit<VHandleOf<Edges>>> + 'a where Visited: 'a {
+        return self.filter_map(|event| match event {
+            DfsEvent::Pre(visit) => Some(visit),
+            DfsEvent::Post(_) => None,
+        });
+    }
+
+    /// Only the `Post` events, i.e. the vertices in finishing order.
+    pub fn post_order(self) -> impl Iterator<Item = Visit<VHandleOf<Edges>>> + 'a where Visited: 'a {
+        return self.filter_map(|event| match event {
+            DfsEvent::Pre(_) => None,
+            DfsEvent::Post(visit) => Some(visit),
+        });
+    }
+
+    fn discover(&mut self, visit: Visit<VHandleOf<Edges>>) -> DfsEvent<VHandleOf<Edges>> {
+        self.visited.visit(visit.vertex);
+        self.stack.push(DfsFrame{visit, edge: 0});
+        self.after_pre = true;
+        return DfsEvent::Pre(visit);
+    }
+}
+
+impl<'a, Edges: EdgeStore, Visited: VisitedSet<VHandleOf<Edges>>> Iterator for Dfs<'a, Edges, Visited> {
+    type Item = DfsEvent<VHandleOf<Edges>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        profile_method!(next);
+        if let Some(start) = self.start.take() {
+            return Some(self.discover(Visit{vertex: start, depth: 0, parent: None}));
+        }
+        loop {
+            let frame = self.stack.last_mut()?;
+            let edges = self.edge_storage.edges(frame.visit.vertex);
+            if frame.edge >= edges.len() {
+                let visit = frame.visit;
+                self.stack.pop();
+                self.after_pre = false;
+                return Some(DfsEvent::Post(visit));
+            }
+            let target = edges[frame.edge].vh();
+            frame.edge += 1;
+            if self.visited.is_visited(target) {
+                continue;
+            }
+            let parent = frame.visit;
+            return Some(self.discover(Visit{vertex: target, depth: parent.depth + 1, parent: Some(parent.vertex)}));
+        }
+    }
+}
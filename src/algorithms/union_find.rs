@@ -0,0 +1,84 @@
+use std::marker::PhantomData;
+use crate::handles::HandleIndex;
+use crate::handles::types::VHandle;
+
+/// Disjoint sets over vertex handles `0..len` with union by rank and path compression.
+#[derive(Debug, Clone)]
+pub struct UnionFind<H = VHandle> {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    set_count: usize,
+    _handle: PhantomData<H>,
+}
+
+impl<H: HandleIndex> UnionFind<H> {
+    /// Every vertex starts in its own set.
+    pub fn new(len: usize) -> Self {
+        return UnionFind{
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+            set_count: len,
+            _handle: PhantomData,
+        }
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn len(&self) -> usize {
+        return self.parents.len();
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn is_empty(&self) -> bool {
+        return self.parents.is_empty();
+    }
+
+    /// Number of disjoint sets.
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn set_count(&self) -> usize {
+        return self.set_count;
+    }
+
+    /// Adds a vertex in its own set and returns its handle, for graphs that grow while being tracked.
+    pub fn push(&mut self) -> H {
+        let vertex = self.parents.len();
+        self.parents.push(vertex);
+        self.ranks.push(0);
+        self.set_count += 1;
+        return H::from_index(vertex);
+    }
+
+    /// Representative of the set containing `vertex`. Every vertex on the way is linked to it directly.
+    pub fn find(&mut self, vertex: H) -> H {
+        let mut root = vertex.index();
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = vertex.index();
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        return H::from_index(root);
+    }
+
+    /// Merges the sets of `a` and `b`. Returns `false` if they already were in the same set.
+    pub fn union(&mut self, a: H, b: H) -> bool {
+        let a = self.find(a).index();
+        let b = self.find(b).index();
+        if a == b {
+            return false;
+        }
+        let (child, root) = if self.ranks[a] < self.ranks[b] { (a, b) } else { (b, a) };
+        self.parents[child] = root;
+        if self.ranks[child] == self.ranks[root] {
+            self.ranks[root] += 1;
+        }
+        self.set_count -= 1;
+        return true;
+    }
+
+    pub fn connected(&mut self, a: H, b: H) -> bool {
+        return self.find(a) == self.find(b);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::handles::HandleIndex;
+use crate::traits::VisitedSet;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// One bit per vertex. Resetting clears the whole set.
+#[derive(Debug, Clone, Default)]
+pub struct VisitedBits {
+    words: Vec<u64>,
+}
+
+impl VisitedBits {
+    pub fn new(vertices_count: usize) -> Self {
+        return VisitedBits{
+            words: vec![0; vertices_count.div_ceil(WORD_BITS)],
+        }
+    }
+}
+
+impl<H: HandleIndex> VisitedSet<H> for VisitedBits {
+    fn reset(&mut self, vertices_count: usize) {
+        self.words.clear();
+        self.words.resize(vertices_count.div_ceil(WORD_BITS), 0);
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn is_visited(&self, vertex: H) -> bool {
+        let index = vertex.index();
+        return self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn visit(&mut self, vertex: H) -> bool {
+        let index = vertex.index();
+        let word = &mut self.words[index / WORD_BITS];
+        let mask = 1 << (index % WORD_BITS);
+        if *word & mask != 0 {
+            return false;
+        }
+        *word |= mask;
+        return true;
+    }
+}
+
+/// One epoch per vertex. A vertex is visited if its epoch equals the current one, so resetting is a single
+/// increment. When the epoch counter wraps around the array is cleared.
+#[derive(Debug, Clone)]
+pub struct VisitedEpochs {
+    pub(in crate) epochs: Vec<u32>,
+    pub(in crate) epoch: u32,
+}
+
+impl VisitedEpochs {
+    pub fn new(vertices_count: usize) -> Self {
+        return VisitedEpochs{
+            epochs: vec![0; vertices_count],
+            epoch: 1,
+        }
+    }
+
+    pub fn epoch(&self) -> u32 {
+        return self.epoch;
+    }
+}
+
+impl Default for VisitedEpochs {
+    fn default() -> Self {
+        return VisitedEpochs::new(0);
+    }
+}
+
+impl<H: HandleIndex> VisitedSet<H> for VisitedEpochs {
+    fn reset(&mut self, vertices_count: usize) {
+        if self.epoch == u32::MAX {
+            self.epochs.fill(0);
+            self.epoch = 0;
+        }
+        self.epoch += 1;
+        self.epochs.resize(vertices_count, 0);
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn is_visited(&self, vertex: H) -> bool {
+        return self.epochs[vertex.index()] == self.epoch;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn visit(&mut self, vertex: H) -> bool {
+        let slot = &mut self.epochs[vertex.index()];
+        if *slot == self.epoch {
+            return false;
+        }
+        *slot = self.epoch;
+        return true;
+    }
+}
@@ -0,0 +1,143 @@
+use firestorm::profile_method;
+use crate::edge_storage::{EdgeStorage, GrowthPolicy, HEADER_SIZE};
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::handles::types::PackedEdge;
+use crate::traits::{EdgeOperator, EdgeStore, Packed, TraverseMarker};
+
+/// Immutable compressed sparse row storage. The edges of vertex `v` are `targets[offsets[v]..offsets[v + 1]]`.
+/// Produced by `Graph::freeze` for read-mostly workloads, there are no headers or reserves between the edge lists.
+pub struct CsrStorage<P = PackedEdge> {
+    pub(in crate) global_visited_flag: usize,
+    pub(in crate) visited: Vec<usize>,
+    pub(in crate) vertex_capacity: usize,
+    pub(in crate) growth_policy: GrowthPolicy,
+    pub(in crate) track_incoming: bool,
+    pub offsets: Vec<usize>,
+    pub targets: Vec<P>,
+}
+
+impl<P: EdgePacking> CsrStorage<P> {
+    /// Packs the edge blocks of `storage` into a CSR layout. Edge order is preserved.
+    pub fn freeze(storage: &EdgeStorage<P>) -> Self {
+        profile_method!(freeze);
+        let vertex_count = storage.indices.len();
+        let mut offsets = Vec::with_capacity(vertex_count + 1);
+        let mut targets = Vec::with_capacity(storage.packed_size(0) - vertex_count * HEADER_SIZE);
+
+        offsets.push(0);
+        for vertex in 0..vertex_count {
+            targets.extend_from_slice(storage.edges(P::VHandle::from_index(vertex)));
+            offsets.push(targets.len());
+        }
+
+        return CsrStorage {
+            global_visited_flag: storage.global_visited_flag,
+            visited: vec![0; vertex_count],
+            vertex_capacity: storage.vertex_capacity,
+            growth_policy: storage.growth_policy,
+            track_incoming: storage.is_tracking_incoming(),
+            offsets,
+            targets,
+        }
+    }
+
+    /// Rebuilds a mutable `EdgeStorage` with the reserve, growth policy and incoming edge tracking
+    /// the storage was frozen with.
+    pub fn thaw(&self) -> EdgeStorage<P> {
+        profile_method!(thaw);
+        let mut storage = EdgeStorage::with_reserve(self.vertex_capacity);
+        storage.set_growth_policy(self.growth_policy);
+        storage.global_visited_flag = self.global_visited_flag;
+
+        // All blocks have to exist before edges can point at them
+        for vertex in 0..self.vertex_count() {
+            storage.extend_edge_storage(self.edges(P::VHandle::from_index(vertex)).len());
+        }
+        for vertex in 0..self.vertex_count() {
+            let handle = P::VHandle::from_index(vertex);
+            storage.add_edges(handle, self.edges(handle));
+        }
+        if self.track_incoming {
+            storage.track_incoming();
+        }
+        return storage;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn vertex_count(&self) -> usize {
+        return self.offsets.len() - 1;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn edge_count(&self) -> usize {
+        return self.targets.len();
+    }
+}
+
+impl<P: EdgePacking> Packed for CsrStorage<P> {
+    type Edge = P;
+}
+
+impl<P: EdgePacking> TraverseMarker for CsrStorage<P> {
+    fn global_visited_flag(&self) -> usize {
+        return self.global_visited_flag;
+    }
+
+    fn begin_traversal(&mut self) {
+        if self.global_visited_flag == usize::MAX {
+            self.visited.fill(0);
+            self.global_visited_flag = 0;
+        }
+        self.global_visited_flag += 1;
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn visited_flag(&self, vertex: P::VHandle) -> usize {
+        return self.visited[vertex.index()];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn set_visited_flag(&mut self, vertex: P::VHandle, val: usize) {
+        self.visited[vertex.index()] = val;
+    }
+}
+
+impl<P: EdgePacking> EdgeStore for CsrStorage<P> {
+    fn edges_offset(&self, vertex: P::VHandle, offset: usize) -> &[P] {
+        profile_method!(edges_from_offset);
+        let start = self.offsets[vertex.index()];
+        let end = self.offsets[vertex.index() + 1];
+        return &self.targets[start + offset..end];
+    }
+
+    fn edges_ptr_offset(&self, vertex: P::VHandle, offset: usize) -> *const P {
+        profile_method!(edges_ptr_offset);
+        let start = self.offsets[vertex.index()];
+        return unsafe {self.targets.as_ptr().add(start + offset)};
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn edges(&self, vertex: P::VHandle) -> &[P] {
+        return self.edges_offset(vertex, 0);
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn edges_ptr(&self, vertex: P::VHandle) -> *const P {
+        return self.edges_ptr_offset(vertex, 0);
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn len(&self, handle: P::VHandle) -> usize {
+        return self.offsets[handle.index() + 1] - self.offsets[handle.index()];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn edge_block_capacity(&self, handle: P::VHandle) -> usize {
+        return self.len(handle);
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn get(&self, vertex: P::VHandle, offset: usize) -> P {
+        return self.targets[self.offsets[vertex.index()] + offset];
+    }
+}
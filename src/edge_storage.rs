@@ -1,299 +1,662 @@
-use std::mem::size_of;
-use std::slice::{from_raw_parts, from_raw_parts_mut};
-use firestorm::{profile_method, profile_section};
+use std::cmp::{max, min};
+use std::mem::{size_of, take};
+use firestorm::{profile_method};
 use crate::graph::{Error};
-use crate::handles::{pack, Slot, vh};
-use crate::handles::types::{VHandle, Weight, PackedEdge};
-use crate::traits::{EdgeOperator, EdgeStore, EdgeStoreMut, TraverseMarker, WeightedEdgeOperator};
-
-const FLAG_OFFSET: Slot = 0;
-const LEN_OFFSET: Slot = 1;
-const CAPACITY_OFFSET: Slot = 2;
-pub const HEADER_SIZE: Slot = 3;
-#[repr(C)]
-pub struct Header{
-    pub visited_flag: VHandle,
-    pub len: VHandle,
-    pub capacity: VHandle,
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::handles::types::PackedEdge;
+use crate::traits::{EdgeOperator, EdgeStore, EdgeStoreMut, IncomingEdgeStore, Packed, PayloadStore, TraverseMarker, WeightedEdgeOperator};
+
+const FLAG_OFFSET: usize = 0;
+const LEN_OFFSET: usize = 1;
+const CAPACITY_OFFSET: usize = 2;
+pub const HEADER_SIZE: usize = 3;
+pub const DEFAULT_GROWTH_FACTOR: usize = 2;
+
+/// Decides what happens when a vertex runs out of space in its edge block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthPolicy {
+    /// The block capacity is fixed at creation time. Overflowing it panics.
+    Fixed,
+    /// The block is relocated to a block with `capacity * factor` slots (or the required size, whichever is larger).
+    Factor(usize),
 }
 
-pub struct EdgeStorage {
-    pub (in crate) global_visited_flag: Slot, // Val used to mark whether the vertex has been visited
-    pub(in crate) vertex_capacity: Slot,
-    pub edges: Vec<Slot>,
-    pub indices: Vec<Slot>, //Todo, make it contain EHandles which are not compatible with VHandles
+/// A block which was left behind after a relocation and can be reused.
+#[derive(Debug, Clone, Copy)]
+pub(in crate) struct FreeBlock {
+    pub offset: usize,
+    pub capacity: usize,
+}
+/// Adjacency stored as one block per vertex inside a single vector. Each block starts with a header of
+/// `HEADER_SIZE` slots (visited flag, length, capacity) followed by the packed edges.
+/// `P` selects the handle width, see `EdgePacking`. `E` is an optional edge payload kept in `payloads`,
+/// a vector aligned slot by slot with `edges`. The default `()` payload takes no memory.
+pub struct EdgeStorage<P = PackedEdge, E = ()> {
+    pub (in crate) global_visited_flag: usize, // Val used to mark whether the vertex has been visited
+    pub(in crate) vertex_capacity: usize,
+    pub(in crate) growth_policy: GrowthPolicy,
+    pub(in crate) free_blocks: Vec<FreeBlock>,
+    pub(in crate) incoming: Option<Box<EdgeStorage<P>>>, // Reverse adjacency, src handles packed with the edge weight
+    pub edges: Vec<P>,
+    pub payloads: Vec<E>,
+    pub indices: Vec<usize>, //Todo, make it contain EHandles which are not compatible with VHandles
 }
 
 
-impl Header {
-    #[allow(unused)]
-    #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn parse_ptr_mut (edges: &mut Vec<Slot>, index: usize) -> (*mut Self, *mut VHandle) {
-        let edges_ptr = edges.as_mut_ptr();
-        unsafe{
-            let header_ptr = edges_ptr.add(index) as *mut Header;
-            let data_ptr = edges_ptr.byte_add(size_of::<Header>()).add(index) as *mut VHandle;
-            return (header_ptr, data_ptr);
+impl<P: EdgePacking, E: Default> EdgeStorage<P, E> {
+
+    /// Creates a new graph with the assumption that the usage will be dynamic.
+    /// It will create the graph with high reserve count of 50 to avoid reallocations.
+    pub fn new_large() -> Self {
+        return Self::with_reserve(50);
+    }
+    /// Creates a new graph with a custom reserve
+    pub fn with_reserve(capacity: usize) -> Self {
+        return EdgeStorage {
+            global_visited_flag: 1,
+            vertex_capacity: capacity,
+            edges: Vec::new(),
+            payloads: Vec::new(),
+            indices: Vec::new(),
+            growth_policy: GrowthPolicy::Factor(DEFAULT_GROWTH_FACTOR),
+            free_blocks: Vec::new(),
+            incoming: None,
         }
     }
-    #[allow(unused)]
+
+    /// Creates a new graph with the assumption that the graph size is known ahead of time. No reserve.
+    pub fn new() -> Self {
+        return Self::with_reserve(0);
+    }
+
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn parse_ptr (edges: &Vec<Slot>, index: usize) -> (*const Self, *const VHandle) {
-        let edges_ptr = edges.as_ptr();
-        unsafe{
-            let header_ptr = edges_ptr.add(index) as *const Header;
-            let data_ptr = edges_ptr.byte_add(size_of::<Header>()).add(index) as *const VHandle;
-            return (header_ptr, data_ptr);
+    fn header(&self, vertex: P::VHandle, offset: usize) -> usize {
+        return self.edges[self.indices[vertex.index()] + offset].index();
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn set_header(&mut self, vertex: P::VHandle, offset: usize, val: usize) {
+        let index = self.indices[vertex.index()];
+        self.edges[index + offset] = P::from_index(val);
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn capacity(&self) -> usize {
+        return self.edges.len();
+    }
+
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        return self.growth_policy;
+    }
+
+    /// Sets how edge blocks behave when they overflow. Already allocated blocks are not touched.
+    pub fn set_growth_policy(&mut self, policy: GrowthPolicy) {
+        self.growth_policy = policy;
+    }
+
+    /// Starts maintaining the reverse adjacency of every vertex. Existing edges are indexed right away,
+    /// later `connect`, `connect_weighted` and `disconnect` calls keep it in sync.
+    pub fn track_incoming(&mut self) {
+        if self.incoming.is_some() {
+            return;
         }
+        let mut incoming = EdgeStorage::new();
+        for _ in 0..self.indices.len() {
+            incoming.extend_edge_storage(0);
+        }
+        for src in 0..self.indices.len() {
+            let src = P::VHandle::from_index(src);
+            for edge in self.edges(src).iter().filter(|edge| edge.vh() != P::NONE) {
+                incoming.add_edges(edge.vh(), &[edge.set_vid(src)]);
+            }
+        }
+        self.incoming = Some(Box::new(incoming));
+    }
+
+    pub fn is_tracking_incoming(&self) -> bool {
+        return self.incoming.is_some();
+    }
+
+    /// The reverse adjacency, if tracked. The edges of a vertex point to its predecessors,
+    /// so a traversal of this storage walks the graph backwards.
+    pub fn incoming(&self) -> Option<&EdgeStorage<P>> {
+        return self.incoming.as_deref();
+    }
+
+    /// Mutable access to the reverse adjacency, meant for traversals which need to mark vertices.
+    /// Connecting or disconnecting through it desynchronizes it from the outgoing edges.
+    pub fn incoming_mut(&mut self) -> Option<&mut EdgeStorage<P>> {
+        return self.incoming.as_deref_mut();
     }
-    pub fn parse_mut (edges: &mut Vec<Slot>, index: usize) -> (&mut Self, &mut [VHandle]) {
-        profile_method!(parse_mut);
-        let edges_ptr = edges.as_mut_ptr();
 
-        // Return as Result instead of panic
-        if index >= edges.len() {
-            panic!("Index out of bounds");
+    /// Swap-removes the first edge of `src` matching `predicate` and returns it.
+    fn remove_edge_where<Predicate>(&mut self, src: P::VHandle, predicate: Predicate) -> Option<P>
+    where Predicate: Fn(P) -> bool {
+        let position = self.edges(src).iter().position(|edge| predicate(*edge))?;
+        return Some(self.remove_edge_at(src, position));
+    }
+
+    /// Swap-removes the last edge of `src` matching `predicate` and returns it.
+    fn remove_last_edge_where<Predicate>(&mut self, src: P::VHandle, predicate: Predicate) -> Option<P>
+    where Predicate: Fn(P) -> bool {
+        let position = self.edges(src).iter().rposition(|edge| predicate(*edge))?;
+        return Some(self.remove_edge_at(src, position));
+    }
+
+    fn remove_edge_at(&mut self, src: P::VHandle, position: usize) -> P {
+        let len = self.len(src);
+        let data = self.edges_mut(src);
+        let removed = data[position];
+        data[position] = data[len - 1]; // Swap the last element for the empty one
+
+        let data_start = self.indices[src.index()] + HEADER_SIZE;
+        self.payloads.swap(data_start + position, data_start + len - 1);
+        self.payloads[data_start + len - 1] = E::default();
+        self.set_header(src, LEN_OFFSET, len - 1);
+        return removed;
+    }
+
+    /// Drops the edges of `src` rejected by `keep` in one pass, keeping the order of the rest.
+    /// The incoming index is not touched. Returns the number of removed edges.
+    fn retain_edges<KeepFunc>(&mut self, src: P::VHandle, keep: KeepFunc) -> usize
+    where KeepFunc: Fn(P) -> bool {
+        let len = self.len(src);
+        let data_start = self.indices[src.index()] + HEADER_SIZE;
+        let mut kept = 0;
+        for position in 0..len {
+            let edge = self.edges[data_start + position];
+            if !keep(edge) {
+                continue;
+            }
+            if kept != position {
+                self.edges[data_start + kept] = edge;
+                self.payloads.swap(data_start + kept, data_start + position);
+            }
+            kept += 1;
+        }
+        self.payloads[data_start + kept..data_start + len].fill_with(Default::default);
+        self.set_header(src, LEN_OFFSET, kept);
+        return len - kept;
+    }
+
+    /// Rewrites `edges` so the blocks are tightly packed in vertex order, each keeping `slack` free slots
+    /// on top of its current length. Free blocks are dropped and the backing allocation is shrunk.
+    /// Returns the number of bytes reclaimed.
+    /// Note that with `GrowthPolicy::Fixed` the shrunk blocks can no longer take more than `slack` new edges.
+    pub fn compact(&mut self, slack: usize) -> usize {
+        profile_method!(compact);
+        let old_bytes = self.edges.capacity() * size_of::<P>();
+        let old_bytes = old_bytes + self.payloads.capacity() * size_of::<E>();
+        let mut edges = Vec::with_capacity(self.packed_size(slack));
+        let mut payloads = Vec::with_capacity(self.packed_size(slack));
+
+        for vertex in 0..self.indices.len() {
+            let offset = self.indices[vertex];
+            let len = self.edges[offset + LEN_OFFSET].index();
+            let block_end = offset + HEADER_SIZE + len;
+
+            let slack = min(slack, P::MAX.index() - len);
+            self.indices[vertex] = edges.len();
+            edges.extend_from_slice(&self.edges[offset..block_end]);
+            edges.resize_with(edges.len() + slack, Default::default);
+            edges[self.indices[vertex] + CAPACITY_OFFSET] = P::from_index(len + slack);
+            payloads.extend(self.payloads[offset..block_end].iter_mut().map(take));
+            payloads.resize_with(edges.len(), Default::default);
+        }
+
+        self.edges = edges;
+        self.payloads = payloads;
+        self.free_blocks.clear();
+        let new_bytes = self.edges.capacity() * size_of::<P>() + self.payloads.capacity() * size_of::<E>();
+        let incoming_bytes = match self.incoming.as_mut() {
+            Some(incoming) => incoming.compact(slack),
+            None => 0,
+        };
+        return old_bytes.saturating_sub(new_bytes) + incoming_bytes;
+    }
+
+    /// Packs the blocks without any slack. Same as `compact(0)`.
+    pub fn shrink_to_fit(&mut self) -> usize {
+        return self.compact(0);
+    }
+
+    /// Number of slots `edges` would take after `compact(slack)`.
+    pub fn packed_size(&self, slack: usize) -> usize {
+        return (0..self.indices.len())
+            .map(|vertex| self.len(P::VHandle::from_index(vertex)))
+            .map(|len| HEADER_SIZE + len + min(slack, P::MAX.index() - len))
+            .sum();
+    }
+
+    /// Finds space for a block with at least `capacity` edges. Free blocks are reused first (best fit),
+    /// otherwise the block is appended to the tail of `edges`. The header of the returned block is zeroed
+    /// apart from the capacity, which may be larger than requested when a free block is reused.
+    fn allocate_block(&mut self, capacity: usize) -> usize {
+        profile_method!(allocate_block);
+        assert!(capacity <= P::MAX.index(), "Block capacity {} does not fit into the block header", capacity);
+        let best_fit = self.free_blocks.iter().enumerate()
+            .filter(|(_, block)| block.capacity >= capacity)
+            .min_by_key(|(_, block)| block.capacity)
+            .map(|(pos, _)| pos);
+
+        if let Some(pos) = best_fit {
+            let block = self.free_blocks.swap_remove(pos);
+            self.edges[block.offset + FLAG_OFFSET] = P::default();
+            self.edges[block.offset + LEN_OFFSET] = P::default();
+            self.edges[block.offset + CAPACITY_OFFSET] = P::from_index(block.capacity);
+            return block.offset;
         }
 
-        unsafe{
-            let header_ptr = edges_ptr.add(index) as *mut Header;
-            let data_ptr = edges_ptr.byte_add(size_of::<Header>()).add(index) as *mut VHandle;
-            let data = from_raw_parts_mut(data_ptr, (*header_ptr).capacity as usize);
-            return (header_ptr.as_mut().unwrap(), data);
+        let offset = self.edges.len();
+        self.edges.resize_with(offset + HEADER_SIZE + capacity, Default::default);
+        self.payloads.resize_with(offset + HEADER_SIZE + capacity, Default::default);
+        self.edges[offset + CAPACITY_OFFSET] = P::from_index(capacity);
+        return offset;
+    }
+
+    /// Moves the edge block of `vertex` into a block with at least `capacity` edges and releases the old one.
+    fn relocate_block(&mut self, vertex: P::VHandle, capacity: usize) {
+        profile_method!(relocate_block);
+        let old_offset = self.indices[vertex.index()];
+        let old_capacity = self.edge_block_capacity(vertex);
+        let len = self.len(vertex);
+
+        let new_offset = self.allocate_block(capacity);
+        self.edges[new_offset + FLAG_OFFSET] = self.edges[old_offset + FLAG_OFFSET];
+        self.edges[new_offset + LEN_OFFSET] = P::from_index(len);
+        let data_start = old_offset + HEADER_SIZE;
+        self.edges.copy_within(data_start..data_start + len, new_offset + HEADER_SIZE);
+        for i in 0..len {
+            self.payloads[new_offset + HEADER_SIZE + i] = take(&mut self.payloads[data_start + i]);
         }
+
+        self.indices[vertex.index()] = new_offset;
+        self.free_blocks.push(FreeBlock{ offset: old_offset, capacity: old_capacity });
+    }
+
+    /// Hands the block of `vertex` over to the free list and points the vertex to a fresh block of `capacity` edges.
+    fn replace_block(&mut self, vertex: P::VHandle, capacity: usize) {
+        self.clear_payloads(vertex);
+        let old_offset = self.indices[vertex.index()];
+        let old_capacity = self.edge_block_capacity(vertex);
+        self.indices[vertex.index()] = self.allocate_block(capacity);
+        self.free_blocks.push(FreeBlock{ offset: old_offset, capacity: old_capacity });
+    }
+
+    /// Drops the payloads of the edges of `vertex`.
+    fn clear_payloads(&mut self, vertex: P::VHandle) {
+        let data_start = self.indices[vertex.index()] + HEADER_SIZE;
+        let len = self.len(vertex);
+        self.payloads[data_start..data_start + len].fill_with(Default::default);
+    }
+
+    /// Edges of `vertex` paired with their payloads.
+    pub fn edges_with_payload(&self, vertex: P::VHandle) -> impl Iterator<Item = (P::VHandle, &E)> {
+        return self.edges(vertex).iter().map(|edge| edge.vh()).zip(self.payloads(vertex).iter());
+    }
+
+    /// Payload of the first edge from `src` to `target`.
+    pub fn payload(&self, src: P::VHandle, target: P::VHandle) -> Option<&E> {
+        let position = self.edges(src).iter().position(|edge| edge.vh() == target)?;
+        return Some(&self.payloads(src)[position]);
     }
-    pub fn parse (edges: &Vec<Slot>, index: usize) -> (&Self, &[VHandle]) {
-        profile_method!(parse);
-        // Return as Result instead of panic
-        let edges_ptr = edges.as_ptr();
 
-        if index >= edges.len() {
-            panic!("Index out of bounds");
+    /// Mutable payload of the first edge from `src` to `target`.
+    pub fn payload_mut(&mut self, src: P::VHandle, target: P::VHandle) -> Option<&mut E> {
+        let position = self.edges(src).iter().position(|edge| edge.vh() == target)?;
+        return Some(&mut self.payloads_mut(src)[position]);
+    }
+
+    /// Makes sure the block of `vertex` can hold `size` edges, growing it according to the growth policy.
+    fn ensure_block_capacity(&mut self, vertex: P::VHandle, size: usize) -> Result<(), Error<P::VHandle>> {
+        let capacity = self.edge_block_capacity(vertex);
+        if size <= capacity {
+            return Ok(());
         }
-        unsafe{
-            let header_ptr = edges_ptr.add(index) as *const Header;
-            let data_ptr = edges_ptr.byte_add(size_of::<Header>()).add(index) as *const VHandle;
-            let data = from_raw_parts(data_ptr, (*header_ptr).len as usize);
-            return (header_ptr.as_ref().unwrap(), data);
+        match self.growth_policy {
+            GrowthPolicy::Fixed => return Err(Error::CapacityExceeded { vertex, capacity }),
+            GrowthPolicy::Factor(factor) => {
+                // Capacity and length are stored as `P` in the header, so a block cannot outgrow `P::MAX`
+                let max_capacity = P::MAX.index();
+                if size > max_capacity {
+                    return Err(Error::CapacityExceeded { vertex, capacity });
+                }
+                let new_capacity = min(max(capacity.saturating_mul(factor), size), max_capacity);
+                self.relocate_block(vertex, new_capacity);
+            }
         }
+        return Ok(());
     }
 }
 
+impl<P: EdgePacking, E: Default> Packed for EdgeStorage<P, E> {
+    type Edge = P;
+}
 
-impl EdgeStorage {
+impl<P: EdgePacking, E: Default> EdgeOperator for EdgeStorage<P, E> {
+    fn add_edges(&mut self, src: P::VHandle, targets: &[P]) {
+        if let Err(error) = self.try_add_edges(src, targets) {
+            panic!("Failed to add edges to {}: {}", src, error);
+        }
+    }
 
-    /// Creates a new graph with the assumption that the usage will be dynamic.
-    /// It will create the graph with high reserve count of 50 to avoid reallocations.
-    pub fn new_large() -> Self {
-        return EdgeStorage {
-            global_visited_flag: 1,
-            vertex_capacity: 50,
-            edges: Vec::new(),
-            indices: Vec::new(),
+    fn try_add_edges(&mut self, src: P::VHandle, targets: &[P]) -> Result<(), Error<P::VHandle>> {
+        if src.index() >= self.indices.len() {
+            return Err(Error::InvalidHandle(src));
+        }
+        // Checked up front so a bad target leaves neither the edges nor the incoming index half written.
+        // `NONE` is let through as the placeholder tree views use for a missing parent.
+        if let Some(target) = targets.iter().find(|target| target.vh() != P::NONE && target.vh().index() >= self.indices.len()) {
+            return Err(Error::InvalidHandle(target.vh()));
+        }
+        let len = self.len(src);
+        let new_size = len + targets.len();
+
+        self.ensure_block_capacity(src, new_size)?;
+
+        let data = self.edges_mut(src);
+        data[len..new_size].copy_from_slice(targets);
+        self.set_header(src, LEN_OFFSET, new_size);
+
+        if let Some(incoming) = self.incoming.as_mut() {
+            for target in targets.iter().filter(|target| target.vh() != P::NONE) {
+                incoming.add_edges(target.vh(), &[target.set_vid(src)]);
+            }
         }
+        return Ok(());
     }
-    /// Creates a new graph with a custom reserve
-    pub fn with_reserve(capacity: Slot) -> Self {
-        return EdgeStorage {
-            global_visited_flag: 1,
-            vertex_capacity: capacity,
-            edges: Vec::new(),
-            indices: Vec::new(),
+
+    fn extend_edge_storage(&mut self, size: usize) -> P::VHandle {
+        let offset = self.allocate_block(self.vertex_capacity + size);
+        self.indices.push(offset);
+        if let Some(incoming) = self.incoming.as_mut() {
+            incoming.extend_edge_storage(0);
         }
+        return P::VHandle::from_index(self.indices.len() - 1);
     }
 
-    /// Creates a new graph with the assumption that the graph size is known ahead of time. No reserve.
-    pub fn new() -> Self {
-        return EdgeStorage {
-            global_visited_flag: 1,
-            vertex_capacity: 0,
-            edges: Vec::new(),
-            indices: Vec::new(),
+    fn release_edge_storage(&mut self, vertex: P::VHandle) {
+        if let Some(incoming) = self.incoming.as_mut() {
+            let start = self.indices[vertex.index()] + HEADER_SIZE;
+            let len = self.edges[self.indices[vertex.index()] + LEN_OFFSET].index();
+            for edge in self.edges[start..start + len].iter().filter(|edge| edge.vh() != P::NONE) {
+                let reverse_edge = edge.set_vid(vertex);
+                incoming.remove_edge_where(edge.vh(), |candidate| candidate == reverse_edge);
+            }
+            incoming.release_edge_storage(vertex);
         }
+
+        if self.edge_block_capacity(vertex) == 0 {
+            self.set_header(vertex, LEN_OFFSET, 0);
+            return;
+        }
+        self.replace_block(vertex, 0);
     }
 
-    fn len_mut_ptr(&mut self, vertex: VHandle) -> *mut Slot {
-        return &mut self.edges[ (self.indices[vertex as usize] + LEN_OFFSET) as usize];
+    fn reuse_edge_storage(&mut self, vertex: P::VHandle, size: usize) {
+        if let Some(incoming) = self.incoming.as_mut() {
+            incoming.reuse_edge_storage(vertex, 0);
+        }
+        let capacity = self.vertex_capacity + size;
+        if self.edge_block_capacity(vertex) < capacity {
+            self.replace_block(vertex, capacity);
+            return;
+        }
+        self.clear_payloads(vertex);
+        self.set_header(vertex, FLAG_OFFSET, 0);
+        self.set_header(vertex, LEN_OFFSET, 0);
     }
 
-    #[allow(unused)]
-    fn reserve_mut(&mut self, vertex: VHandle) -> &mut Slot {
-        let edge_chunk_index = self.indices[vertex as usize];
-        return &mut self.edges[ (edge_chunk_index + CAPACITY_OFFSET) as usize];
+    fn disconnect(&mut self, src: P::VHandle, target: P::VHandle) {
+        let removed = self.remove_edge_where(src, |edge| edge.vh() == target);
+        if let (Some(removed), Some(incoming)) = (removed, self.incoming.as_mut()) {
+            if target == P::NONE {
+                return;
+            }
+            let reverse_edge = removed.set_vid(src);
+            incoming.remove_edge_where(target, |edge| edge == reverse_edge);
+        }
     }
-    #[cfg_attr(not(debug_assertions), inline(always))]
-    fn len_mut(&mut self, vertex: VHandle) -> &mut Slot {
-        let edge_chunk_index = self.indices[vertex as usize];
-        return &mut self.edges[ (edge_chunk_index + LEN_OFFSET) as usize];
+
+    fn disconnect_last(&mut self, src: P::VHandle, target: P::VHandle) {
+        let removed = self.remove_last_edge_where(src, |edge| edge.vh() == target);
+        if let (Some(removed), Some(incoming)) = (removed, self.incoming.as_mut()) {
+            if target == P::NONE {
+                return;
+            }
+            let reverse_edge = removed.set_vid(src);
+            incoming.remove_last_edge_where(target, |edge| edge == reverse_edge);
+        }
+    }
+
+    fn disconnect_all(&mut self, src: P::VHandle, target: P::VHandle) {
+        let removed = self.retain_edges(src, |edge| edge.vh() != target);
+        if removed == 0 || target == P::NONE {
+            return;
+        }
+        if let Some(incoming) = self.incoming.as_mut() {
+            incoming.retain_edges(target, |edge| edge.vh() != src);
+        }
+    }
+
+    fn disconnect_incoming<SourceFunc: Fn(P::VHandle) -> bool>(&mut self, target: P::VHandle, is_source: SourceFunc) {
+        profile_method!(disconnect_incoming);
+        if let Some(mut incoming) = self.incoming.take() {
+            let mut sources: Vec<P::VHandle> = incoming.edges(target).iter().map(|edge| edge.vh()).collect();
+            sources.sort_unstable();
+            sources.dedup();
+            for src in sources {
+                self.retain_edges(src, |edge| edge.vh() != target);
+            }
+            incoming.retain_edges(target, |_| false);
+            self.incoming = Some(incoming);
+            return;
+        }
+        for src in (0..self.indices.len()).map(P::VHandle::from_index).filter(|src| is_source(*src)) {
+            self.retain_edges(src, |edge| edge.vh() != target);
+        }
+    }
+
+    fn try_disconnect(&mut self, src: P::VHandle, target: P::VHandle) -> Result<(), Error<P::VHandle>> {
+        if src.index() >= self.indices.len() {
+            return Err(Error::InvalidHandle(src));
+        }
+        if !self.edges(src).iter().any(|edge| edge.vh() == target) {
+            return Err(Error::EdgeNotFound { src, target });
+        }
+        self.disconnect(src, target);
+        return Ok(());
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn calculate_new_edges_size_abs(&self, size: Slot) -> Slot {
-        let header_size = HEADER_SIZE;
-        return (self.edges.len() as Slot + self.vertex_capacity + header_size + size) as Slot;
+    fn connect(&mut self, src: P::VHandle, target: P::VHandle) {
+        self.add_edges(src, &[P::vh_pack(target)]);
     }
+
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn capacity(&self) -> Slot {
-        return self.edges.len() as Slot;
+    fn try_connect(&mut self, src: P::VHandle, target: P::VHandle) -> Result<(), Error<P::VHandle>> {
+        return self.try_add_edges(src, &[P::vh_pack(target)]);
     }
 }
 
-impl EdgeOperator for EdgeStorage {
-    fn add_edges(&mut self, src: VHandle, targets: &[PackedEdge]) {
-        let len = self.len(src) as usize;
-        let new_size = len + targets.len();
+impl<P: EdgePacking, E: Default> WeightedEdgeOperator for EdgeStorage<P, E> {
+    fn connect_weighted(&mut self, from: P::VHandle, to: P::VHandle, weight: P::Weight) {
+        self.add_edges(from, &[P::pack(to, weight)]);
+    }
+
+    fn try_connect_weighted(&mut self, from: P::VHandle, to: P::VHandle, weight: P::Weight) -> Result<(), Error<P::VHandle>> {
+        return self.try_add_edges(from, &[P::pack(to, weight)]);
+    }
 
-        // TODO return as Result instead of panic!
-        if new_size > self.edge_block_capacity(src) as usize {
-            panic!("Edge size is greater than the allocated size");
+    fn try_set_weight(&mut self, from: P::VHandle, to: P::VHandle, weight: P::Weight) -> Result<(), Error<P::VHandle>> {
+        if from.index() >= self.indices.len() {
+            return Err(Error::InvalidHandle(from));
+        }
+        let Some(position) = self.edges(from).iter().position(|edge| edge.vh() == to) else {
+            return Err(Error::EdgeNotFound { src: from, target: to });
+        };
+        let edge = &mut self.edges_mut(from)[position];
+        let old_edge = *edge;
+        *edge = edge.set_wgt(weight);
+
+        if let Some(incoming) = self.incoming.as_mut() {
+            let reverse_edge = old_edge.set_vid(from);
+            let len = incoming.len(to);
+            if let Some(edge) = incoming.edges_mut(to)[..len].iter_mut().find(|edge| **edge == reverse_edge) {
+                *edge = edge.set_wgt(weight);
+            }
         }
+        return Ok(());
+    }
+}
 
-        let data = self.edges_mut(src);
-        data[len..new_size].copy_from_slice(targets);
-        *self.len_mut(src) = new_size as Slot;
+impl<P: EdgePacking, E: Default> PayloadStore for EdgeStorage<P, E> {
+    type Payload = E;
+
+    fn payloads(&self, vertex: P::VHandle) -> &[E] {
+        let data_start = self.indices[vertex.index()] + HEADER_SIZE;
+        return &self.payloads[data_start..data_start + self.len(vertex)];
     }
 
-    fn extend_edge_storage(&mut self, size: Slot) -> Slot {
-        let offset = self.edges.len() as Slot;
-        let val = self.calculate_new_edges_size_abs(size);
-        self.edges.resize_with(val as usize, Default::default);
-        self.edges[ (offset + CAPACITY_OFFSET) as usize] = self.vertex_capacity + size;
-        self.indices.push(offset);
-        return (self.indices.len() - 1) as Slot;
-    }
-
-    fn disconnect(&mut self, src: VHandle, target: VHandle) {
-        let data = self.edges_mut_ptr(src);
-        let len = self.len_mut_ptr(src);
-        unsafe {
-            let mut iter = data;
-            let end = iter.add(*len as usize);
-            while iter != end{
-                if vh(*iter) == target {
-                    *iter = *end.offset(-1 ); // Swap the last element for the empty one
-                    *len -= 1;
-                    break;
-                }
-                iter = iter.offset(1);
-            }
+    fn payloads_mut(&mut self, vertex: P::VHandle) -> &mut [E] {
+        let data_start = self.indices[vertex.index()] + HEADER_SIZE;
+        let len = self.len(vertex);
+        return &mut self.payloads[data_start..data_start + len];
+    }
+
+    fn connect_with(&mut self, from: P::VHandle, to: P::VHandle, payload: E) {
+        if let Err(error) = self.try_connect_with(from, to, payload) {
+            panic!("Failed to connect {} to {}: {}", from, to, error);
         }
     }
-    #[cfg_attr(not(debug_assertions), inline(always))]
-    fn connect(&mut self, src: VHandle, target: VHandle) {
-        self.add_edges(src, &[pack(target, 0)]);
+
+    fn try_connect_with(&mut self, from: P::VHandle, to: P::VHandle, payload: E) -> Result<(), Error<P::VHandle>> {
+        return self.try_connect_weighted_with(from, to, P::Weight::default(), payload);
+    }
+
+    fn connect_weighted_with(&mut self, from: P::VHandle, to: P::VHandle, weight: P::Weight, payload: E) {
+        if let Err(error) = self.try_connect_weighted_with(from, to, weight, payload) {
+            panic!("Failed to connect {} to {}: {}", from, to, error);
+        }
     }
-}
 
-impl WeightedEdgeOperator for EdgeStorage{
-    fn connect_weighted(&mut self, from: VHandle, to: VHandle, weight: Weight) {
-        self.add_edges(from, &[pack(to, weight)]);
+    fn try_connect_weighted_with(&mut self, from: P::VHandle, to: P::VHandle, weight: P::Weight, payload: E) -> Result<(), Error<P::VHandle>> {
+        self.try_connect_weighted(from, to, weight)?;
+        let len = self.len(from);
+        self.payloads_mut(from)[len - 1] = payload;
+        return Ok(());
     }
 }
 
-impl TraverseMarker for EdgeStorage {
-    fn global_visited_flag(&self) -> Slot {
-        return self.global_visited_flag;
+impl<P: EdgePacking, E: Default> IncomingEdgeStore for EdgeStorage<P, E> {
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn in_edges(&self, vertex: P::VHandle) -> &[P] {
+        return self.incoming.as_ref().expect("Incoming edges are not tracked").edges(vertex);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn inc_global_visited_flag(&mut self) {
-        self.global_visited_flag += 1;
+    fn in_degree(&self, vertex: P::VHandle) -> usize {
+        return self.incoming.as_ref().expect("Incoming edges are not tracked").len(vertex);
     }
+}
 
-    fn reset_global_visited_flag(&mut self) {
-        self.global_visited_flag = 0;
+impl<P: EdgePacking, E: Default> TraverseMarker for EdgeStorage<P, E> {
+    fn global_visited_flag(&self) -> usize {
+        return self.global_visited_flag;
     }
 
-    #[cfg_attr(not(debug_assertions), inline(always))]
-    fn visited_flag(&self, vertex: VHandle) -> Slot {
-        profile_method!(visited_flag_fast);
-        let edge_chunk_index = self.indices[vertex as usize] as usize;
-        return self.edges[ (edge_chunk_index as Slot + FLAG_OFFSET) as usize];
+    fn begin_traversal(&mut self) {
+        profile_method!(begin_traversal);
+        if self.global_visited_flag >= P::MAX.index() { // Flags are stored as P, the next epoch would wrap
+            for &offset in &self.indices {
+                self.edges[offset + FLAG_OFFSET] = P::default();
+            }
+            self.global_visited_flag = 0;
+        }
+        self.global_visited_flag += 1;
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn inc_visited_flag(&mut self, vertex: VHandle) {
-        profile_method!(inc_visited_flag_fast);
-        let edge_chunk_index = self.indices[vertex as usize] as usize;
-        self.edges[ (edge_chunk_index as Slot + FLAG_OFFSET) as usize] += 1;
+    fn visited_flag(&self, vertex: P::VHandle) -> usize {
+        profile_method!(visited_flag_fast);
+        return self.header(vertex, FLAG_OFFSET);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn set_visited_flag(&mut self, vertex: VHandle, val: Slot) {
-        profile_method!(inc_visited_flag_fast);
-        let edge_chunk_index = self.indices[vertex as usize];
-        self.edges[ (edge_chunk_index + FLAG_OFFSET) as usize] = val;
+    fn set_visited_flag(&mut self, vertex: P::VHandle, val: usize) {
+        profile_method!(set_visited_flag_fast);
+        self.set_header(vertex, FLAG_OFFSET, val);
     }
 }
 
-impl EdgeStore for EdgeStorage {
-    fn edges_offset(&self, vertex: VHandle, offset: Slot) -> &[PackedEdge] {
+impl<P: EdgePacking, E: Default> EdgeStore for EdgeStorage<P, E> {
+    fn edges_offset(&self, vertex: P::VHandle, offset: usize) -> &[P] {
         profile_method!(edges_from_offset);
-        let edge_chunk_index = self.indices[vertex as usize];
-        let len = self.edges[ (edge_chunk_index + LEN_OFFSET) as usize];
-        let data = &self.edges[ (offset + edge_chunk_index + HEADER_SIZE) as usize.. (edge_chunk_index + HEADER_SIZE + len) as usize];
+        let edge_chunk_index = self.indices[vertex.index()];
+        let len = self.edges[edge_chunk_index + LEN_OFFSET].index();
+        let data = &self.edges[offset + edge_chunk_index + HEADER_SIZE..edge_chunk_index + HEADER_SIZE + len];
         return data;
     }
-    fn edges_ptr_offset(&self, vertex: VHandle, offset: Slot) -> *const PackedEdge {
+    fn edges_ptr_offset(&self, vertex: P::VHandle, offset: usize) -> *const P {
         profile_method!(edges_ptr_offset);
-        let edge_chunk_index = self.indices[vertex as usize];
-        return unsafe {self.edges.as_ptr().add((offset + edge_chunk_index + HEADER_SIZE) as usize)}
+        let edge_chunk_index = self.indices[vertex.index()];
+        return unsafe {self.edges.as_ptr().add(offset + edge_chunk_index + HEADER_SIZE)}
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn edges(&self, vertex: VHandle) -> &[PackedEdge] {
+    fn edges(&self, vertex: P::VHandle) -> &[P] {
         return self.edges_offset(vertex, 0);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn edges_ptr(&self, vertex: VHandle) -> *const PackedEdge {
+    fn edges_ptr(&self, vertex: P::VHandle) -> *const P {
         return self.edges_ptr_offset(vertex, 0);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn len(&self, handle: VHandle) -> Slot {
-        return self.edges[ (self.indices[handle as usize] + LEN_OFFSET) as usize];
+    fn len(&self, handle: P::VHandle) -> usize {
+        return self.header(handle, LEN_OFFSET);
     }
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn edge_block_capacity(&self, handle: VHandle) -> Slot {
-        return self.edges[ (self.indices[handle as usize] + CAPACITY_OFFSET) as usize];
+    fn edge_block_capacity(&self, handle: P::VHandle) -> usize {
+        return self.header(handle, CAPACITY_OFFSET);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn get(&self, vertex: VHandle, offset: Slot) -> PackedEdge {
-        let index = self.indices[vertex as usize];
-        return self.edges[ ( index + HEADER_SIZE + offset) as usize];
+    fn get(&self, vertex: P::VHandle, offset: usize) -> P {
+        let index = self.indices[vertex.index()];
+        return self.edges[index + HEADER_SIZE + offset];
     }
 }
-impl EdgeStoreMut for EdgeStorage {
-    fn edges_mut_offset(&mut self, vertex: VHandle, offset: Slot) -> &mut [PackedEdge] {
+impl<P: EdgePacking, E: Default> EdgeStoreMut for EdgeStorage<P, E> {
+    fn edges_mut_offset(&mut self, vertex: P::VHandle, offset: usize) -> &mut [P] {
         profile_method!(edges_mut_from_offset);
-        let edge_chunk_index = self.indices[vertex as usize];
-        let reserve = self.edges[ (edge_chunk_index + CAPACITY_OFFSET) as usize];
-        let data = &mut self.edges[ (offset + edge_chunk_index + HEADER_SIZE) as usize..(edge_chunk_index + HEADER_SIZE + reserve) as usize];
+        let edge_chunk_index = self.indices[vertex.index()];
+        let reserve = self.edges[edge_chunk_index + CAPACITY_OFFSET].index();
+        let data = &mut self.edges[offset + edge_chunk_index + HEADER_SIZE..edge_chunk_index + HEADER_SIZE + reserve];
         return data;
     }
-    fn edges_mut_ptr_offset(&mut self, vertex: VHandle, offset: Slot) -> *mut PackedEdge {
+    fn edges_mut_ptr_offset(&mut self, vertex: P::VHandle, offset: usize) -> *mut P {
         profile_method!(edges_mut_ptr_offset);
-        let edge_chunk_index = self.indices[vertex as usize];
-        let data = &mut self.edges[ (offset + edge_chunk_index + HEADER_SIZE) as usize];
+        let edge_chunk_index = self.indices[vertex.index()];
+        let data = &mut self.edges[offset + edge_chunk_index + HEADER_SIZE];
         return data;
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn edges_mut_ptr(&mut self, vertex: VHandle) -> *mut PackedEdge {
+    fn edges_mut_ptr(&mut self, vertex: P::VHandle) -> *mut P {
         return self.edges_mut_ptr_offset(vertex, 0);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn edges_mut(&mut self, vertex: VHandle) -> &mut [PackedEdge] {
+    fn edges_mut(&mut self, vertex: P::VHandle) -> &mut [P] {
         return self.edges_mut_offset(vertex, 0);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    fn set(&mut self, src: VHandle, val: PackedEdge, offset: Slot) {
-        let index = self.indices[src as usize];
-        self.edges[ (index + offset + HEADER_SIZE) as usize] = val;
+    fn set(&mut self, src: P::VHandle, val: P, offset: usize) {
+        let index = self.indices[src.index()];
+        self.edges[index + offset + HEADER_SIZE] = val;
     }
 
-}
\ No newline at end of file
+}
@@ -1,21 +1,54 @@
 use std::cmp::min;
+use std::fmt::{Display, Formatter};
 use std::ops::{Index, IndexMut};
 use std::thread::available_parallelism;
+use crate::csr_storage::CsrStorage;
 use crate::edge_storage::{EdgeStorage};
-use crate::handles::Slot;
-use crate::handles::types::{VHandle, Weight};
+use crate::handles::{EdgePacking, HandleIndex, StableHandle};
+use crate::handles::types::{VHandle};
 use crate::traits;
-use crate::traits::{EdgeOperator, EdgeStore, EdgeStoreMut, TraverseMarker};
+use crate::traits::{EdgeOf, EdgeOperator, EdgeStoreMut, TraverseMarker, VHandleOf};
 use crate::utils::{split_to_parts_mut};
 use crate::views::tree::TreeView;
 
-#[derive(Debug)]
-pub enum Error {
-    NoHandle,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<H = VHandle> {
+    /// The handle does not point to a live vertex.
+    InvalidHandle(H),
+    /// The edge block of `vertex` is full and the growth policy does not allow it to grow.
+    CapacityExceeded { vertex: H, capacity: usize },
+    /// Every representable `VHandle` is already in use.
+    HandleSpaceExhausted,
+    /// There is no edge from `src` to `target`.
+    EdgeNotFound { src: H, target: H },
+    /// The edge from `src` to `target` already exists and the graph does not allow parallel edges.
+    EdgeExists { src: H, target: H },
+    /// The index does not point into the edge storage.
+    IndexOutOfBounds(usize),
 }
 
+impl<H: HandleIndex> Display for Error<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Error::InvalidHandle(vertex) => write!(f, "vertex {} does not exist", vertex),
+            Error::CapacityExceeded { vertex, capacity } => write!(f, "edge block of vertex {} is full (capacity {})", vertex, capacity),
+            Error::HandleSpaceExhausted => write!(f, "no vertex handles left"),
+            Error::EdgeNotFound { src, target } => write!(f, "no edge from {} to {}", src, target),
+            Error::EdgeExists { src, target } => write!(f, "edge from {} to {} already exists", src, target),
+            Error::IndexOutOfBounds(index) => write!(f, "index {} is out of bounds", index),
+        };
+    }
+}
+
+impl<H: HandleIndex> std::error::Error for Error<H> {}
+
+/// Vertex values together with the generation of every slot.
+/// Even generations mark live slots, odd generations mark removed slots waiting in `free_slots` to be reused.
+/// Removed slots hold `None`.
 pub struct Vertices<VertexType> {
-    data: Vec<VertexType>,
+    data: Vec<Option<VertexType>>,
+    generations: Vec<usize>,
+    free_slots: Vec<usize>,
 }
 
 pub struct Graph<VertexType, EdgeStorageType> {
@@ -31,7 +64,7 @@ impl<VertexType> Graph<VertexType, EdgeStorage> {
             vertices: Vertices::new(),
         }
     }
-    pub fn with_reserve(reserve: Slot) -> Self {
+    pub fn with_reserve(reserve: usize) -> Self {
         return Graph{
             edges: EdgeStorage::with_reserve(reserve),
             vertices: Vertices::new(),
@@ -45,7 +78,37 @@ impl<VertexType> Graph<VertexType, EdgeStorage> {
             vertices: Vertices::new(),
         };
     }
+}
 
+impl<VertexType, EdgeStorageType> Graph<VertexType, EdgeStorageType> {
+    /// Creates an empty graph on top of `edges`. This is how graphs with a non-default handle width are made,
+    /// e.g. `Graph::with_storage(EdgeStorage::<u16>::new())`.
+    pub fn with_storage(edges: EdgeStorageType) -> Self {
+        return Graph{
+            edges,
+            vertices: Vertices::new(),
+        };
+    }
+}
+
+impl<VertexType, P: EdgePacking> Graph<VertexType, EdgeStorage<P>> {
+    /// Converts the graph into a read-only graph backed by `CsrStorage`.
+    pub fn freeze(self) -> Graph<VertexType, CsrStorage<P>> {
+        return Graph{
+            edges: CsrStorage::freeze(&self.edges),
+            vertices: self.vertices,
+        };
+    }
+}
+
+impl<VertexType, P: EdgePacking> Graph<VertexType, CsrStorage<P>> {
+    /// Converts a frozen graph back into a mutable one.
+    pub fn thaw(self) -> Graph<VertexType, EdgeStorage<P>> {
+        return Graph{
+            edges: self.edges.thaw(),
+            vertices: self.vertices,
+        };
+    }
 }
 
 impl<VertexType, EdgeStorageType> Graph<VertexType, EdgeStorageType>
@@ -54,7 +117,7 @@ where EdgeStorageType: EdgeStoreMut+ EdgeOperator + TraverseMarker {
         return TreeView::new(&mut self.edges, &mut self.vertices);
     }
 
-    pub fn create_and_connect(&mut self, src_vertex: VHandle, val: VertexType, edge_count: Slot) -> VHandle {
+    pub fn create_and_connect(&mut self, src_vertex: VHandleOf<EdgeStorageType>, val: VertexType, edge_count: usize) -> VHandleOf<EdgeStorageType> {
         let new_vertex = self.create(val, edge_count);
         self.edges.connect(src_vertex, new_vertex);
         return new_vertex;
@@ -65,31 +128,124 @@ where EdgeStorageType: EdgeStoreMut+ EdgeOperator + TraverseMarker {
     //     self.edges.connect_weighted(src_vertex, new_vertex, weight);
     //     return new_vertex;
     // }
-    pub fn create_and_connect_leaf(&mut self, src_vertex: VHandle, val: VertexType) -> VHandle {
+    pub fn create_and_connect_leaf(&mut self, src_vertex: VHandleOf<EdgeStorageType>, val: VertexType) -> VHandleOf<EdgeStorageType> {
         return self.create_and_connect(src_vertex, val, 0);
     }
 
-    pub fn create(&mut self, val: VertexType, edge_count: Slot) -> VHandle {
+    /// Creates a new vertex with room for `edge_count` edges on top of the storage reserve.
+    /// Slots of removed vertices are reused before the graph grows.
+    pub fn create(&mut self, val: VertexType, edge_count: usize) -> VHandleOf<EdgeStorageType> {
+        if let Some(vertex) = self.vertices.free_slot() {
+            self.vertices.revive(vertex, val);
+            self.edges.reuse_edge_storage(vertex, edge_count);
+            return vertex;
+        }
         self.vertices.push(val);
-        let new_vertex = (self.vertices.len() - 1)  as VHandle;
+        let new_vertex = VHandleOf::<EdgeStorageType>::from_index(self.vertices.len() - 1);
         self.edges.extend_edge_storage(edge_count);
         return new_vertex;
     }
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn create_leaf(&mut self, val: VertexType) -> VHandle {
+    pub fn create_leaf(&mut self, val: VertexType) -> VHandleOf<EdgeStorageType> {
         return self.create(val, 0)
     }
+
+    /// Same as `create`, but fails instead of handing out `NONE` or wrapping around once the handle space runs out.
+    pub fn try_create(&mut self, val: VertexType, edge_count: usize) -> Result<VHandleOf<EdgeStorageType>, Error<VHandleOf<EdgeStorageType>>> {
+        if self.vertices.free_slot::<VHandleOf<EdgeStorageType>>().is_none() && self.vertices.len() >= EdgeOf::<EdgeStorageType>::NONE.index() {
+            return Err(Error::HandleSpaceExhausted);
+        }
+        return Ok(self.create(val, edge_count));
+    }
+
+    pub fn try_connect(&mut self, src: VHandleOf<EdgeStorageType>, target: VHandleOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        self.check_handle(src)?;
+        self.check_handle(target)?;
+        return self.edges.try_connect(src, target);
+    }
+
+    pub fn try_disconnect(&mut self, src: VHandleOf<EdgeStorageType>, target: VHandleOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        self.check_handle(src)?;
+        self.check_handle(target)?;
+        return self.edges.try_disconnect(src, target);
+    }
+
+    pub fn try_edges(&self, vertex: VHandleOf<EdgeStorageType>) -> Result<&[EdgeOf<EdgeStorageType>], Error<VHandleOf<EdgeStorageType>>> {
+        self.check_handle(vertex)?;
+        return Ok(self.edges.edges(vertex));
+    }
+
+    pub fn try_get(&self, vertex: VHandleOf<EdgeStorageType>) -> Result<&VertexType, Error<VHandleOf<EdgeStorageType>>> {
+        self.check_handle(vertex)?;
+        return Ok(&self.vertices[vertex]);
+    }
+
+    pub fn try_get_mut(&mut self, vertex: VHandleOf<EdgeStorageType>) -> Result<&mut VertexType, Error<VHandleOf<EdgeStorageType>>> {
+        self.check_handle(vertex)?;
+        return Ok(&mut self.vertices[vertex]);
+    }
+
+    pub fn try_remove(&mut self, vertex: VHandleOf<EdgeStorageType>) -> Result<VertexType, Error<VHandleOf<EdgeStorageType>>> {
+        self.check_handle(vertex)?;
+        return Ok(self.remove(vertex));
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub(in crate) fn check_handle(&self, vertex: VHandleOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        if !self.vertices.is_alive(vertex) {
+            return Err(Error::InvalidHandle(vertex));
+        }
+        return Ok(());
+    }
+
+    /// Removes `vertex` together with its outgoing and incoming edges and returns its value. The slot stays
+    /// empty until it is reused. Every `StableHandle` taken before the removal becomes stale.
+    /// Panics if the vertex has already been removed.
+    pub fn remove(&mut self, vertex: VHandleOf<EdgeStorageType>) -> VertexType {
+        if !self.vertices.is_alive(vertex) {
+            panic!("Vertex has already been removed");
+        }
+        let value = self.vertices.remove(vertex);
+        // Before the release, which also drops the incoming edges of the vertex
+        let vertices = &self.vertices;
+        self.edges.disconnect_incoming(vertex, |src| vertices.is_alive(src));
+        self.edges.release_edge_storage(vertex);
+        return value;
+    }
+
+    /// Returns a handle which can later be checked for staleness with `resolve`.
+    pub fn stable_handle(&self, vertex: VHandleOf<EdgeStorageType>) -> StableHandle<VHandleOf<EdgeStorageType>> {
+        return StableHandle{ vertex, generation: self.vertices.generation(vertex) };
+    }
+
+    /// Returns the vertex behind `handle` or `None` if the vertex has been removed since the handle was taken.
+    pub fn resolve(&self, handle: StableHandle<VHandleOf<EdgeStorageType>>) -> Option<VHandleOf<EdgeStorageType>> {
+        if !self.vertices.is_alive(handle.vertex) || self.vertices.generation(handle.vertex) != handle.generation {
+            return None;
+        }
+        return Some(handle.vertex);
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn is_alive(&self, vertex: VHandleOf<EdgeStorageType>) -> bool {
+        return self.vertices.is_alive(vertex);
+    }
 }
 
 
+/// Only live values are handed to `transform_fn`, in slot order. They are moved into one slice for the call
+/// and back into their slots afterwards.
 impl <T: Send> traits::Transformer<T> for Vertices<T> {
     fn transform(&mut self, transform_fn: fn(&mut [T])) {
-        transform_fn(self.data.as_mut_slice());
+        let mut live = self.take_live();
+        transform_fn(live.as_mut_slice());
+        self.restore_live(live);
     }
     fn async_transform(&mut self, transform_fn: fn(&mut [T])) {
+        let mut live = self.take_live();
         let max_parallelism = available_parallelism().ok().unwrap().get();
-        let parallelism_count = min(max_parallelism, self.data.len());
-        let parts = split_to_parts_mut(&mut self.data, parallelism_count);
+        let parallelism_count = min(max_parallelism, live.len()).max(1);
+        let parts = split_to_parts_mut(&mut live, parallelism_count);
 
         std::thread::scope(|scope| {
             for part in parts {
@@ -99,7 +255,7 @@ impl <T: Send> traits::Transformer<T> for Vertices<T> {
             }
         });
 
-
+        self.restore_live(live);
     }
 
 }
@@ -107,27 +263,99 @@ impl <T> Vertices<T>{
     pub fn new() -> Self {
         return Vertices{
             data: Vec::new(),
+            generations: Vec::new(),
+            free_slots: Vec::new(),
         }
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
     pub fn push(&mut self, val: T) {
-        self.data.push(val);
+        self.data.push(Some(val));
+        self.generations.push(0);
+    }
+
+    /// Appends a slot which starts out removed, for graphs mirroring the layout of another graph.
+    pub(in crate) fn push_removed(&mut self) {
+        self.data.push(None);
+        self.generations.push(1);
+        self.free_slots.push(self.data.len() - 1);
     }
+
+    /// Number of slots, including the slots of removed vertices.
     pub fn len(&self) -> usize {
         return self.data.len();
     }
+
+    /// Number of live vertices.
+    pub fn count(&self) -> usize {
+        return self.data.len() - self.free_slots.len();
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn generation<H: HandleIndex>(&self, vertex: H) -> usize {
+        return self.generations[vertex.index()];
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn is_alive<H: HandleIndex>(&self, vertex: H) -> bool {
+        return vertex.index() < self.data.len() && self.generations[vertex.index()].is_multiple_of(2);
+    }
+
+    /// The value of `vertex`, `None` if it has been removed.
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn get<H: HandleIndex>(&self, vertex: H) -> Option<&T> {
+        return self.data.get(vertex.index()).and_then(|value| value.as_ref());
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    pub fn get_mut<H: HandleIndex>(&mut self, vertex: H) -> Option<&mut T> {
+        return self.data.get_mut(vertex.index()).and_then(|value| value.as_mut());
+    }
+
+    /// The slot the next `revive` will fill, if any vertex has been removed.
+    pub fn free_slot<H: HandleIndex>(&self) -> Option<H> {
+        return self.free_slots.last().map(|slot| H::from_index(*slot));
+    }
+
+    /// Stores `val` in the slot returned by `free_slot`.
+    pub(in crate) fn revive<H: HandleIndex>(&mut self, vertex: H, val: T) {
+        let slot = self.free_slots.pop().expect("No free slot to revive");
+        debug_assert_eq!(slot, vertex.index());
+        self.data[vertex.index()] = Some(val);
+        self.generations[vertex.index()] += 1;
+    }
+
+    /// Moves the live values out of their slots, in slot order.
+    fn take_live(&mut self) -> Vec<T> {
+        return self.data.iter_mut().filter_map(Option::take).collect();
+    }
+
+    /// Puts the values returned by `take_live` back into the live slots.
+    fn restore_live(&mut self, live: Vec<T>) {
+        let live_slots = self.data.iter_mut().zip(&self.generations).filter(|(_, generation)| generation.is_multiple_of(2));
+        for ((slot, _), value) in live_slots.zip(live) {
+            *slot = Some(value);
+        }
+    }
+
+    pub(in crate) fn remove<H: HandleIndex>(&mut self, vertex: H) -> T {
+        let value = self.data[vertex.index()].take().expect("Vertex has already been removed");
+        self.generations[vertex.index()] += 1;
+        self.free_slots.push(vertex.index());
+        return value;
+    }
 }
 
-impl <T> Index<VHandle> for Vertices<T>{
+impl <T, H: HandleIndex> Index<H> for Vertices<T>{
     type Output = T;
-    fn index(&self, index: VHandle) -> &Self::Output {
-        return &self.data[index as usize];
+    /// Panics if the vertex has been removed.
+    fn index(&self, index: H) -> &Self::Output {
+        return self.data[index.index()].as_ref().expect("Vertex has been removed");
     }
 }
 
-impl <T> IndexMut<VHandle> for Vertices<T>{
-    fn index_mut(&mut self, index: VHandle) -> &mut Self::Output {
-        return &mut self.data[index as usize];
+impl <T, H: HandleIndex> IndexMut<H> for Vertices<T>{
+    fn index_mut(&mut self, index: H) -> &mut Self::Output {
+        return self.data[index.index()].as_mut().expect("Vertex has been removed");
     }
-}
\ No newline at end of file
+}
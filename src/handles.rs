@@ -1,63 +1,129 @@
-use std::ops::AddAssign;
-use crate::handles::types::{MASK, SHIFT, VHandle, Weight, PackedEdge};
-#[cfg(msize_type = "u16")]
-pub mod types{
-    pub type PackedEdge = u16;
-    pub type Weight = i8;
-    pub type VHandle = u8;
-    pub(in crate::handles) const SHIFT: usize = 8;
-    pub(in crate::handles) const MASK: u8 = 0xFF;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// The default handle width, used when no `EdgePacking` is named. Storages can be instantiated with any
+/// other packing, e.g. `EdgeStorage::<u16>::new()`.
+pub mod types {
+    pub type PackedEdge = u64;
+    pub type Weight = i32;
+    pub type VHandle = u32;
 }
 
+use crate::handles::types::{VHandle, Weight, PackedEdge};
 
-#[cfg(msize_type = "u32")]
-pub mod types{
-    pub type PackedEdge = u32;
-    pub type Weight = i16;
-    pub type VHandle = u16;
-    pub(in crate::handles) const SHIFT: usize = 16;
-    pub(in crate::handles) const MASK: u16 = 0xFFFF;
+pub type Slot = PackedEdge;
+
+pub const NONE: VHandle = VHandle::MAX;
 
+/// An unsigned integer usable as a vertex handle or as a packed edge.
+pub trait HandleIndex: Copy + Eq + Ord + Hash + Debug + Display + Default + Send + Sync + 'static {
+    const MAX: Self;
+    fn index(self) -> usize;
+    fn from_index(index: usize) -> Self;
 }
 
+/// A packed edge holding the target `VHandle` in the low half and the `Weight` in the high half.
+pub trait EdgePacking: HandleIndex {
+    type VHandle: HandleIndex;
+    type Weight: Copy + Debug + Default + PartialEq + Send + Sync + Into<i64> + 'static;
+    const NONE: Self::VHandle;
 
+    fn pack(vertex: Self::VHandle, weight: Self::Weight) -> Self;
+    fn vh(self) -> Self::VHandle;
+    fn wgt(self) -> Self::Weight;
+    fn vh_pack(vertex: Self::VHandle) -> Self;
+    fn set_wgt(self, weight: Self::Weight) -> Self;
+    fn set_vid(self, vertex: Self::VHandle) -> Self;
+}
 
-#[cfg(msize_type = "u64")]
-pub mod types {
-    pub type PackedEdge = u64;
-    pub type Weight = i32;
-    pub type VHandle = u32;
-    pub(in crate::handles) const SHIFT: usize = 32;
-    pub(in crate::handles) const MASK: u32 = 0xFFFFFFFF;
+macro_rules! impl_handle_index {
+    ($($index:ty),*) => {$(
+        impl HandleIndex for $index {
+            const MAX: Self = <$index>::MAX;
+            #[inline(always)]
+            fn index(self) -> usize {
+                self as usize
+            }
+            #[inline(always)]
+            fn from_index(index: usize) -> Self {
+                debug_assert!(<$index>::try_from(index).is_ok(), "{} does not fit into {}", index, stringify!($index));
+                index as $index
+            }
+        }
+    )*};
 }
 
-pub type Slot = PackedEdge;
+macro_rules! impl_edge_packing {
+    ($packed:ty, $vhandle:ty, $weight:ty, $shift:expr) => {
+        impl EdgePacking for $packed {
+            type VHandle = $vhandle;
+            type Weight = $weight;
+            const NONE: $vhandle = <$vhandle>::MAX;
 
-pub const NONE: VHandle = VHandle::MAX;
+            #[inline(always)]
+            fn pack(vertex: $vhandle, weight: $weight) -> Self {
+                (vertex as $packed) | ((weight as $packed) << $shift)
+            }
+            #[inline(always)]
+            fn vh(self) -> $vhandle {
+                self as $vhandle
+            }
+            #[inline(always)]
+            fn wgt(self) -> $weight {
+                (self >> $shift) as $weight
+            }
+            #[inline(always)]
+            fn vh_pack(vertex: $vhandle) -> Self {
+                vertex as $packed
+            }
+            #[inline(always)]
+            fn set_wgt(self, weight: $weight) -> Self {
+                (self & (<$vhandle>::MAX as $packed)) | ((weight as $packed) << $shift)
+            }
+            #[inline(always)]
+            fn set_vid(self, vertex: $vhandle) -> Self {
+                (self & !(<$vhandle>::MAX as $packed)) | (vertex as $packed)
+            }
+        }
+    };
+}
+
+impl_handle_index!(u8, u16, u32, u64);
+impl_edge_packing!(u16, u8, i8, 8);
+impl_edge_packing!(u32, u16, i16, 16);
+impl_edge_packing!(u64, u32, i32, 32);
+
+/// A vertex handle paired with the generation of its slot. Unlike a plain `VHandle` it can tell whether the
+/// vertex it was taken from has been removed in the meantime, even when the slot got reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StableHandle<H = VHandle> {
+    pub vertex: H,
+    pub generation: usize,
+}
 
 #[inline(always)]
 pub fn vh(handle: PackedEdge) -> VHandle {
-    handle as VHandle
+    handle.vh()
 }
 
 #[inline(always)]
 pub fn wgt(handle: PackedEdge) -> Weight {
-    (handle >> SHIFT) as Weight
+    handle.wgt()
 }
 #[inline(always)]
 pub fn vh_pack(handle: VHandle) -> PackedEdge {
-    handle as PackedEdge
+    PackedEdge::vh_pack(handle)
 }
 
 #[inline(always)]
 pub fn pack(node_id: VHandle, weight: Weight) -> PackedEdge {
-    (node_id as PackedEdge) | ((weight as PackedEdge) << SHIFT)
+    PackedEdge::pack(node_id, weight)
 }
 #[inline(always)]
 pub fn set_wgt(handle: PackedEdge, weight: Weight) -> PackedEdge {
-    (handle & (MASK as PackedEdge)) | ((weight as PackedEdge) << SHIFT)
+    handle.set_wgt(weight)
 }
 #[inline(always)]
 pub fn set_vid(handle: PackedEdge, vert_id: VHandle) -> PackedEdge {
-    (handle & !(MASK as PackedEdge)) | (vert_id as PackedEdge)
-}
\ No newline at end of file
+    handle.set_vid(vert_id)
+}
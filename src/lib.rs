@@ -9,7 +9,9 @@ mod tests;
 #[cfg(test)]
 mod bench;
 mod prelude;
-mod edge_storage;
-mod algorithms;
-mod handles;
-mod weighted_graph;
\ No newline at end of file
+pub mod edge_storage;
+pub mod csr_storage;
+pub mod algorithms;
+pub mod handles;
+pub mod weighted_graph;
+pub mod undirected_graph;
\ No newline at end of file
@@ -0,0 +1,211 @@
+use crate::algorithms::{condense, condense_with, strongly_connected_components, strongly_connected_components_with, topological_sort, weakly_connected_components, weakly_connected_components_with, UnionFind};
+use crate::edge_storage::EdgeStorage;
+use crate::graph::Graph;
+use crate::handles::types::VHandle;
+use crate::handles::{vh, wgt};
+use crate::traits::{EdgeOperator, EdgeStore, WeightedEdgeOperator};
+use crate::undirected_graph::UndirectedGraph;
+
+/// {0, 1, 2} and {3, 4} are cycles, 5 and 6 are on their own.
+fn build_graph() -> Graph<usize, EdgeStorage> {
+    let mut graph = Graph::new();
+    for i in 0..7 {
+        graph.create(i, 2);
+    }
+    let edges = &mut graph.edges;
+    edges.connect_weighted(0, 1, 1);
+    edges.connect_weighted(1, 2, 1);
+    edges.connect_weighted(2, 0, 1);
+    edges.connect_weighted(2, 3, 7);
+    edges.connect_weighted(1, 3, 4);
+    edges.connect_weighted(3, 4, 1);
+    edges.connect_weighted(4, 3, 1);
+    edges.connect_weighted(4, 5, 2);
+    edges.connect_weighted(0, 5, 9);
+    edges.connect_weighted(6, 6, 1);
+    return graph;
+}
+
+#[test]
+pub fn scc_test(){
+    let graph = build_graph();
+    let vertices_count = graph.vertices.len();
+    let components = strongly_connected_components(&graph.edges, vertices_count);
+    assert_eq!(components.count, 4);
+    assert!(components.same_component(0 as VHandle, 2 as VHandle));
+    assert!(components.same_component(3 as VHandle, 4 as VHandle));
+    assert!(!components.same_component(2 as VHandle, 3 as VHandle));
+    assert!(!components.same_component(4 as VHandle, 5 as VHandle));
+
+    // Ids follow the topological order of the condensation
+    for vertex in 0..vertices_count as VHandle {
+        for edge in graph.edges.edges(vertex) {
+            assert!(components.component(vertex) <= components.component(vh(*edge)));
+        }
+    }
+    let mut groups: Vec<Vec<VHandle>> = components.groups();
+    groups.sort();
+    assert_eq!(groups, vec![vec![0, 1, 2], vec![3, 4], vec![5], vec![6]]);
+}
+
+#[test]
+pub fn scc_deep_test(){
+    // A single cycle through 200k vertices would overflow a recursive implementation
+    let vertices_count = 200_000;
+    let mut graph: Graph<(), EdgeStorage> = Graph::new();
+    for _ in 0..vertices_count {
+        graph.create((), 1);
+    }
+    for vertex in 0..vertices_count {
+        graph.edges.connect_weighted(vertex as VHandle, ((vertex + 1) % vertices_count) as VHandle, 0);
+    }
+    let components = strongly_connected_components(&graph.edges, vertices_count);
+    assert_eq!(components.count, 1);
+
+    graph.edges.disconnect(0, 1);
+    let components = strongly_connected_components(&graph.edges, vertices_count);
+    assert_eq!(components.count, vertices_count);
+}
+
+#[test]
+pub fn condense_test(){
+    let graph = build_graph();
+    let vertices_count = graph.vertices.len();
+    let (condensed, components) = condense(&graph.edges, vertices_count);
+    assert_eq!(condensed.vertices.len(), 4);
+    assert!(topological_sort(&condensed.edges, 4).is_ok());
+
+    let first = components.component(0 as VHandle) as VHandle;
+    let second = components.component(3 as VHandle) as VHandle;
+    let single = components.component(5 as VHandle) as VHandle;
+    let looped = components.component(6 as VHandle) as VHandle;
+    assert_eq!(condensed.vertices[first], vec![0, 1, 2]);
+    assert_eq!(condensed.vertices[second], vec![3, 4]);
+    assert_eq!(condensed.vertices[looped], vec![6]);
+
+    // The two links from the first cycle into the second one keep the lighter weight
+    let mut links: Vec<(VHandle, i32)> = condensed.edges.edges(first).iter().map(|edge| (vh(*edge), wgt(*edge))).collect();
+    links.sort();
+    assert_eq!(links, vec![(second, 4), (single, 9)]);
+    let links: Vec<(VHandle, i32)> = condensed.edges.edges(second).iter().map(|edge| (vh(*edge), wgt(*edge))).collect();
+    assert_eq!(links, vec![(single, 2)]);
+    assert_eq!(condensed.edges.len(looped), 0);
+}
+
+#[test]
+pub fn condense_removed_vertex_test(){
+    let mut graph = build_graph();
+    let vertices_count = graph.vertices.len();
+    graph.remove(4);
+    graph.remove(6);
+
+    assert_eq!(condense(&graph.edges, vertices_count).0.vertices.len(), 5);
+    let (condensed, components) = condense_with(&graph.edges, vertices_count, |vertex| graph.is_alive(vertex));
+    assert_eq!(condensed.vertices.len(), 3);
+    assert_eq!(components.component(4 as VHandle), usize::MAX);
+    assert_eq!(components.component(6 as VHandle), usize::MAX);
+
+    let first = components.component(0 as VHandle) as VHandle;
+    let third = components.component(3 as VHandle) as VHandle;
+    let single = components.component(5 as VHandle) as VHandle;
+    assert_eq!(condensed.vertices[first], vec![0, 1, 2]);
+    assert_eq!(condensed.vertices[third], vec![3]);
+    let links: Vec<(VHandle, i32)> = condensed.edges.edges(first).iter().map(|edge| (vh(*edge), wgt(*edge))).collect();
+    assert_eq!(links, vec![(single, 9), (third, 4)]);
+    assert_eq!(condensed.edges.len(third), 0);
+}
+
+#[test]
+pub fn condense_memory_test(){
+    // Every vertex of a chain is its own component, the condensed blocks must not reserve room per component
+    let vertices_count = 2000;
+    let mut graph: Graph<usize, EdgeStorage> = Graph::new();
+    for i in 0..vertices_count {
+        graph.create(i, 1);
+    }
+    for vertex in 0..vertices_count - 1 {
+        graph.edges.connect_weighted(vertex as VHandle, (vertex + 1) as VHandle, 1);
+    }
+    let (condensed, components) = condense(&graph.edges, vertices_count);
+    assert_eq!(components.count, vertices_count);
+    assert_eq!(condensed.edges.capacity(), condensed.edges.packed_size(0));
+    assert!(condensed.edges.capacity() <= graph.edges.capacity());
+}
+
+#[test]
+pub fn components_removed_vertex_test(){
+    let mut graph: Graph<usize, EdgeStorage> = Graph::new();
+    let a = graph.create(0, 1);
+    let b = graph.create_and_connect(a, 1, 1);
+    let c = graph.create_and_connect(b, 2, 1);
+    graph.remove(c);
+    let vertices_count = graph.vertices.len();
+
+    // The plain functions see the dead slot as a vertex of its own
+    assert_eq!(weakly_connected_components(&graph.edges, vertices_count).count, 2);
+    let weak = weakly_connected_components_with(&graph.edges, vertices_count, |vertex| graph.is_alive(vertex));
+    assert_eq!(weak.count, 1);
+    assert_eq!(weak.component(c), usize::MAX);
+    assert!(!weak.same_component(c, c));
+    assert_eq!(weak.groups::<VHandle>(), vec![vec![a, b]]);
+
+    let strong = strongly_connected_components_with(&graph.edges, vertices_count, |vertex| graph.is_alive(vertex));
+    assert_eq!(strong.count, 2);
+    assert_eq!(strong.groups::<VHandle>(), vec![vec![a], vec![b]]);
+    assert_eq!(strong.component(c), usize::MAX);
+}
+
+#[test]
+pub fn union_find_test(){
+    let mut sets: UnionFind = UnionFind::new(8);
+    assert_eq!(sets.set_count(), 8);
+    assert!(sets.union(0, 1));
+    assert!(sets.union(2, 3));
+    assert!(sets.union(1, 3));
+    assert!(!sets.union(0, 2));
+    assert_eq!(sets.set_count(), 5);
+    assert!(sets.connected(0, 3));
+    assert!(!sets.connected(0, 4));
+    assert_eq!(sets.find(2), sets.find(1));
+
+    let new = sets.push();
+    assert_eq!(new, 8);
+    assert_eq!(sets.len(), 9);
+    assert_eq!(sets.set_count(), 6);
+    assert!(sets.union(new, 0));
+    assert!(sets.connected(8, 2));
+}
+
+#[test]
+pub fn union_find_chain_test(){
+    // Union by rank keeps the trees flat, a long chain of unions still resolves to one root
+    let count = 100_000;
+    let mut sets: UnionFind<u32> = UnionFind::new(count);
+    for vertex in 1..count as u32 {
+        sets.union(vertex - 1, vertex);
+    }
+    assert_eq!(sets.set_count(), 1);
+    let root = sets.find(0);
+    for vertex in 0..count as u32 {
+        assert_eq!(sets.find(vertex), root);
+    }
+}
+
+#[test]
+pub fn wcc_test(){
+    let graph = build_graph();
+    let components = weakly_connected_components(&graph.edges, graph.vertices.len());
+    assert_eq!(components.count, 2);
+    assert_eq!(components.labels, vec![0, 0, 0, 0, 0, 0, 1]);
+
+    let mut graph: UndirectedGraph<usize, EdgeStorage> = UndirectedGraph::new();
+    for i in 0..6 {
+        graph.graph.create(i, 2);
+    }
+    graph.connect(0, 3);
+    graph.connect(4, 1);
+    graph.connect(3, 5);
+    let components = weakly_connected_components(&graph.graph.edges, graph.graph.vertices.len());
+    assert_eq!(components.count, 3);
+    assert_eq!(components.groups::<VHandle>(), vec![vec![0, 3, 5], vec![1, 4], vec![2]]);
+}
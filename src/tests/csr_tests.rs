@@ -0,0 +1,73 @@
+use crate::algorithms::{bfs, dfs};
+use crate::algorithms::ControlFlow::Resume;
+use crate::tests::{build_tree_graph, TreeGraph};
+use crate::handles::types::VHandle;
+use crate::handles::vh;
+use crate::traits::{EdgeOperator, EdgeStore};
+
+#[test]
+pub fn csr_freeze_test(){
+    let TreeGraph{graph, root} = build_tree_graph();
+    let expected: Vec<Vec<VHandle>> = (0..graph.vertices.len() as VHandle)
+        .map(|vertex| graph.edges.edges(vertex).iter().map(|edge| vh(*edge)).collect())
+        .collect();
+
+    let frozen = graph.freeze();
+    assert_eq!(frozen.edges.vertex_count(), 8);
+    assert_eq!(frozen.edges.edge_count(), 7);
+    assert_eq!(frozen.edges.targets.len(), 7);
+    assert_eq!(frozen.edges.len(root), 3);
+    assert_eq!(frozen.edges.edge_block_capacity(root), 3);
+
+    for (vertex, edges) in expected.iter().enumerate() {
+        let frozen_edges: Vec<VHandle> = frozen.edges.edges(vertex as VHandle).iter().map(|edge| vh(*edge)).collect();
+        assert_eq!(&frozen_edges, edges);
+    }
+}
+
+#[test]
+pub fn csr_traversal_test(){
+    let TreeGraph{mut graph, root} = build_tree_graph();
+    let mut bfs_order = Vec::new();
+    bfs(&mut graph.edges, root, graph.vertices.len(), |_edges, handle|{
+        bfs_order.push(handle);
+        Resume
+    });
+    let mut dfs_order = Vec::new();
+    dfs(&mut graph.edges, root, graph.vertices.len(), |_edges, handle|{
+        dfs_order.push(handle);
+        Resume
+    }, |_edges, _handle|{});
+
+    let mut frozen = build_tree_graph().graph.freeze();
+
+    let mut frozen_bfs_order = Vec::new();
+    bfs(&mut frozen.edges, root, frozen.vertices.len(), |_edges, handle|{
+        frozen_bfs_order.push(handle);
+        Resume
+    });
+    let mut frozen_dfs_order = Vec::new();
+    dfs(&mut frozen.edges, root, frozen.vertices.len(), |_edges, handle|{
+        frozen_dfs_order.push(handle);
+        Resume
+    }, |_edges, _handle|{});
+
+    assert_eq!(bfs_order.len(), 8);
+    assert_eq!(bfs_order, frozen_bfs_order);
+    assert_eq!(dfs_order, frozen_dfs_order);
+}
+
+#[test]
+pub fn csr_thaw_test(){
+    let TreeGraph{graph, root} = build_tree_graph();
+    let mut graph = graph.freeze().thaw();
+
+    assert_eq!(graph.edges.len(root), 3);
+    assert_eq!(graph.edges.edge_block_capacity(root), 53);
+    assert_eq!(graph.vertices[root], "root");
+
+    let d = graph.create_and_connect_leaf(root, "d");
+    graph.edges.connect(d, root);
+    assert_eq!(vh(graph.edges.edges(root)[3]), d);
+    assert_eq!(vh(graph.edges.edges(d)[0]), root);
+}
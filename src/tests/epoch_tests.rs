@@ -0,0 +1,119 @@
+use crate::algorithms::{bfs, dfs};
+use crate::algorithms::ControlFlow::{Continue, End, Resume};
+use crate::edge_storage::EdgeStorage;
+use crate::graph::Graph;
+use crate::handles::HandleIndex;
+use crate::traits::{EdgeOperator, EdgeStore, EdgeStoreMut, TraverseMarker, VHandleOf};
+
+/// Every vertex reaches every other one through the ring, plus chords that create diamonds.
+fn build_ring<S>(mut graph: Graph<usize, S>, vertices_count: usize) -> Graph<usize, S>
+where S: EdgeStoreMut + EdgeOperator + TraverseMarker {
+    for i in 0..vertices_count {
+        graph.create(i, 3);
+    }
+    for i in 0..vertices_count {
+        let from = VHandleOf::<S>::from_index(i);
+        graph.edges.connect(from, VHandleOf::<S>::from_index((i + 1) % vertices_count));
+        graph.edges.connect(from, VHandleOf::<S>::from_index((i + 2) % vertices_count));
+        graph.edges.connect(from, VHandleOf::<S>::from_index((i * 7) % vertices_count));
+    }
+    return graph;
+}
+
+fn bfs_count<S: EdgeStore + TraverseMarker>(edges: &mut S, start: VHandleOf<S>, vertices_count: usize) -> usize {
+    let mut count = 0;
+    bfs(edges, start, vertices_count, |_edges, _handle|{
+        count += 1;
+        Resume
+    });
+    return count;
+}
+
+fn dfs_count<S: EdgeStore + TraverseMarker>(edges: &mut S, start: VHandleOf<S>, vertices_count: usize) -> usize {
+    let mut pre = 0;
+    let mut post = 0;
+    dfs(edges, start, vertices_count, |_edges, _handle|{
+        pre += 1;
+        Resume
+    }, |_edges, _handle|{
+        post += 1;
+    });
+    assert_eq!(pre, post);
+    return pre;
+}
+
+#[test]
+pub fn epoch_back_to_back_test(){
+    let vertices_count = 50;
+    let mut graph = build_ring(Graph::new(), vertices_count);
+    for i in 0..5000 {
+        let start = (i % vertices_count) as u32;
+        assert_eq!(bfs_count(&mut graph.edges, start, vertices_count), vertices_count);
+        assert_eq!(dfs_count(&mut graph.edges, start, vertices_count), vertices_count);
+    }
+}
+
+#[test]
+pub fn epoch_aborted_traversal_test(){
+    let vertices_count = 50;
+    let mut graph = build_ring(Graph::new(), vertices_count);
+    for i in 0..5000 {
+        let start = (i % vertices_count) as u32;
+        let limit = i % 7;
+        let mut seen = 0;
+        if i % 2 == 0 {
+            bfs(&mut graph.edges, start, vertices_count, |_edges, _handle|{
+                seen += 1;
+                if seen > limit { End } else { Resume }
+            });
+        } else {
+            dfs(&mut graph.edges, start, vertices_count, |_edges, _handle|{
+                seen += 1;
+                if seen > limit { End } else { Resume }
+            }, |_edges, _handle|{});
+        }
+        assert_eq!(seen, limit + 1);
+
+        let other = ((i * 13) % vertices_count) as u32;
+        assert_eq!(bfs_count(&mut graph.edges, other, vertices_count), vertices_count);
+        assert_eq!(dfs_count(&mut graph.edges, other, vertices_count), vertices_count);
+    }
+}
+
+#[test]
+pub fn epoch_pruned_traversal_test(){
+    let vertices_count = 50;
+    let mut graph = build_ring(Graph::new(), vertices_count);
+    let mut pruned = 0;
+    bfs(&mut graph.edges, 0, vertices_count, |_edges, handle|{
+        pruned += 1;
+        if handle == 0 { Resume } else { Continue }
+    });
+    assert_eq!(pruned, 3); // Root plus its neighbours 1 and 2, the third edge is a self loop
+    assert_eq!(bfs_count(&mut graph.edges, 0, vertices_count), vertices_count);
+}
+
+#[test]
+pub fn epoch_wraparound_test(){
+    // Flags of a u16 storage wrap after 65535 epochs
+    let vertices_count = 20;
+    let mut graph = build_ring(Graph::with_storage(EdgeStorage::<u16>::new()), vertices_count);
+    for i in 0..70000usize {
+        let start = (i % vertices_count) as u8;
+        assert_eq!(bfs_count(&mut graph.edges, start, vertices_count), vertices_count);
+    }
+    assert!(graph.edges.global_visited_flag() < 70000 - 65535 + 2);
+    assert_eq!(dfs_count(&mut graph.edges, 3, vertices_count), vertices_count);
+}
+
+#[test]
+pub fn epoch_csr_wraparound_test(){
+    let vertices_count = 20;
+    let mut graph = build_ring(Graph::new(), vertices_count).freeze();
+    graph.edges.global_visited_flag = usize::MAX - 2;
+    for i in 0..10 {
+        assert_eq!(bfs_count(&mut graph.edges, i, vertices_count), vertices_count);
+        assert_eq!(dfs_count(&mut graph.edges, i, vertices_count), vertices_count);
+    }
+    assert!(graph.edges.global_visited_flag() < 20);
+}
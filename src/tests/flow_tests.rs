@@ -0,0 +1,245 @@
+use crate::algorithms::{dinic, hungarian, hungarian_graph, min_cost_flow, min_cost_max_flow, push_relabel, MaxFlow, NegativeCycle};
+use crate::edge_storage::EdgeStorage;
+use crate::handles::types::VHandle;
+use crate::handles::{vh, wgt};
+use crate::graph::Graph;
+use crate::traits::{EdgeStore, PayloadStore, WeightedEdgeOperator};
+use crate::weighted_graph::WeightedGraph;
+
+/// The CLRS network with a maximum flow of 23 from 0 to 5.
+fn build_network() -> WeightedGraph<(), EdgeStorage> {
+    let mut graph = WeightedGraph::new();
+    for _ in 0..6 {
+        graph.graph.create((), 2);
+    }
+    let links = [(0, 1, 16), (0, 2, 13), (1, 3, 12), (2, 1, 4), (2, 4, 14), (3, 2, 9), (3, 5, 20), (4, 3, 7), (4, 5, 4)];
+    for (src, target, capacity) in links {
+        graph.graph.edges.connect_weighted(src, target, capacity);
+    }
+    return graph;
+}
+
+/// Capacities hold, flow is conserved and the cut is as large as the flow.
+fn assert_valid_flow(edges: &EdgeStorage, flow: &MaxFlow<VHandle>, source: VHandle, sink: VHandle, vertices_count: usize) {
+    let mut balance = vec![0i64; vertices_count];
+    for vertex in 0..vertices_count as VHandle {
+        for (edge, amount) in edges.edges(vertex).iter().zip(flow.edge_flows(vertex)) {
+            assert!(*amount >= 0 && *amount <= (wgt(*edge) as i64).max(0));
+            balance[vertex as usize] -= amount;
+            balance[vh(*edge) as usize] += amount;
+        }
+    }
+    for vertex in 0..vertices_count {
+        let expected = if vertex == source as usize { -flow.value } else if vertex == sink as usize { flow.value } else { 0 };
+        assert_eq!(balance[vertex], expected);
+    }
+
+    assert!(flow.is_source_side(source));
+    assert!(!flow.is_source_side(sink));
+    let cut_capacity: i64 = flow.cut.iter().map(|(src, target)| {
+        edges.edges(*src).iter().filter(|edge| vh(**edge) == *target).map(|edge| (wgt(*edge) as i64).max(0)).sum::<i64>()
+    }).sum();
+    assert_eq!(cut_capacity, flow.value);
+}
+
+#[test]
+pub fn max_flow_test(){
+    let graph = build_network();
+    let edges = &graph.graph.edges;
+    for flow in [dinic(edges, 0, 5, 6), push_relabel(edges, 0, 5, 6)] {
+        assert_eq!(flow.value, 23);
+        assert_valid_flow(edges, &flow, 0, 5, 6);
+        let mut source_side: Vec<VHandle> = (0..6).filter(|vertex| flow.is_source_side(*vertex)).collect();
+        source_side.sort();
+        assert_eq!(source_side, vec![0, 1, 2, 4]);
+        let mut cut = flow.cut.clone();
+        cut.sort();
+        assert_eq!(cut, vec![(1, 3), (4, 3), (4, 5)]);
+    }
+}
+
+#[test]
+pub fn max_flow_disconnected_test(){
+    let graph = build_network();
+    let edges = &graph.graph.edges;
+    for flow in [dinic(edges, 5, 0, 6), push_relabel(edges, 5, 0, 6), dinic(edges, 0, 0, 6)] {
+        assert_eq!(flow.value, 0);
+        assert!(flow.flows.iter().all(|amount| *amount == 0));
+        assert!(flow.cut.is_empty());
+    }
+}
+
+#[test]
+pub fn max_flow_random_test(){
+    let vertices_count = 60;
+    let mut seed: u64 = 7;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        return (seed >> 33) as usize;
+    };
+    for round in 0..20 {
+        let mut graph: WeightedGraph<(), EdgeStorage> = WeightedGraph::new();
+        for _ in 0..vertices_count {
+            graph.graph.create((), 4);
+        }
+        for _ in 0..vertices_count * (2 + round % 5) {
+            let src = (next() % vertices_count) as VHandle;
+            let target = (next() % vertices_count) as VHandle;
+            let capacity = (next() % 30) as i32 - 3; // Some edges without capacity
+            graph.graph.edges.connect_weighted(src, target, capacity);
+        }
+        let edges = &graph.graph.edges;
+        let source = (round % vertices_count) as VHandle;
+        let sink = (vertices_count - 1 - round) as VHandle;
+        let layered = dinic(edges, source, sink, vertices_count);
+        let pushed = push_relabel(edges, source, sink, vertices_count);
+        assert_eq!(layered.value, pushed.value);
+        assert_valid_flow(edges, &layered, source, sink, vertices_count);
+        assert_valid_flow(edges, &pushed, source, sink, vertices_count);
+    }
+}
+
+/// Capacity as weight and cost per unit as payload.
+fn build_cost_network() -> Graph<(), EdgeStorage<u64, i64>> {
+    let mut graph = Graph::with_storage(EdgeStorage::<u64, i64>::new());
+    for _ in 0..4 {
+        graph.create((), 2);
+    }
+    let links = [(0, 1, 2, 1), (0, 2, 1, 2), (1, 2, 1, 1), (1, 3, 1, 3), (2, 3, 2, 1)];
+    for (src, target, capacity, cost) in links {
+        graph.edges.connect_weighted_with(src, target, capacity, cost);
+    }
+    return graph;
+}
+
+#[test]
+pub fn min_cost_flow_test(){
+    let graph = build_cost_network();
+    let result = min_cost_max_flow(&graph.edges, 0, 3, 4).unwrap();
+    assert_eq!(result.flow.value, 3);
+    assert_eq!(result.cost, 10);
+    assert_eq!(result.flow.edge_flows(0), &[2, 1]);
+    assert_eq!(result.flow.edge_flows(2), &[2]);
+
+    let cheapest = min_cost_flow(&graph.edges, 0, 3, 4, 1, |vertex, offset| graph.edges.payloads(vertex)[offset]).unwrap();
+    assert_eq!(cheapest.flow.value, 1);
+    assert_eq!(cheapest.cost, 3);
+
+    // A negative cost makes the longer route the cheaper one
+    let negative = min_cost_flow(&graph.edges, 0, 3, 4, 1, |vertex, offset| if (vertex, offset) == (1, 1) { -5 } else { 1 }).unwrap();
+    assert_eq!(negative.cost, -4);
+    assert_eq!(negative.flow.edge_flow(1, 1), 1);
+}
+
+#[test]
+pub fn min_cost_flow_negative_cycle_test(){
+    let mut graph = Graph::with_storage(EdgeStorage::<u64, i64>::new());
+    for _ in 0..3 {
+        graph.create((), 2);
+    }
+    graph.edges.connect_weighted_with(0, 1, 1, -3);
+    graph.edges.connect_weighted_with(1, 0, 1, -3);
+    graph.edges.connect_weighted_with(1, 2, 1, 1);
+    assert_eq!(min_cost_max_flow(&graph.edges, 0, 2, 3), Err(NegativeCycle{vertices: vec![0, 1]}));
+
+    // Without capacity on the way back the cycle cannot carry flow and does not count
+    let mut graph = Graph::with_storage(EdgeStorage::<u64, i64>::new());
+    for _ in 0..3 {
+        graph.create((), 2);
+    }
+    graph.edges.connect_weighted_with(0, 1, 1, -3);
+    graph.edges.connect_weighted_with(1, 0, 0, -3);
+    graph.edges.connect_weighted_with(1, 2, 1, 1);
+    assert_eq!(min_cost_max_flow(&graph.edges, 0, 2, 3).unwrap().cost, -2);
+}
+
+fn brute_force_assignment(costs: &[Vec<i64>]) -> i64 {
+    fn search(costs: &[Vec<i64>], row: usize, taken: &mut Vec<bool>) -> i64 {
+        if row == costs.len() {
+            return 0;
+        }
+        let mut best = i64::MAX;
+        for column in 0..costs[row].len() {
+            if !taken[column] {
+                taken[column] = true;
+                best = best.min(costs[row][column] + search(costs, row + 1, taken));
+                taken[column] = false;
+            }
+        }
+        return best;
+    }
+    return search(costs, 0, &mut vec![false; costs[0].len()]);
+}
+
+#[test]
+pub fn hungarian_test(){
+    let costs = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+    let assignment = hungarian(&costs);
+    assert_eq!(assignment.cost, 5);
+    assert_eq!(assignment.columns, vec![1, 0, 2]);
+    assert_eq!(hungarian(&[]).cost, 0);
+
+    let mut seed: u64 = 3;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        return (seed >> 33) as i64 % 50 - 10;
+    };
+    for (rows, columns) in [(6, 6), (3, 5), (1, 4), (7, 7)] {
+        let costs: Vec<Vec<i64>> = (0..rows).map(|_| (0..columns).map(|_| next()).collect()).collect();
+        let assignment = hungarian(&costs);
+        assert_eq!(assignment.cost, brute_force_assignment(&costs));
+        let mut columns_used = assignment.columns.clone();
+        columns_used.sort();
+        columns_used.dedup();
+        assert_eq!(columns_used.len(), rows);
+    }
+}
+
+#[test]
+pub fn assignment_as_flow_test(){
+    // Jobs 1..=4 onto workers 5..=8, source 0 and sink 9, the flow solution matches the Hungarian one
+    let costs = vec![vec![9, 2, 7, 8], vec![6, 4, 3, 7], vec![5, 8, 1, 8], vec![7, 6, 9, 4]];
+    let mut graph = Graph::with_storage(EdgeStorage::<u64, i64>::new());
+    for _ in 0..10 {
+        graph.create((), 4);
+    }
+    for job in 0..4 {
+        graph.edges.connect_weighted_with(0, 1 + job, 1, 0);
+        graph.edges.connect_weighted_with(5 + job, 9, 1, 0);
+        for worker in 0..4 {
+            graph.edges.connect_weighted_with(1 + job, 5 + worker, 1, costs[job as usize][worker as usize]);
+        }
+    }
+    let result = min_cost_max_flow(&graph.edges, 0, 9, 10).unwrap();
+    assert_eq!(result.flow.value, 4);
+    assert_eq!(result.cost, 13);
+    assert_eq!(hungarian(&costs).cost, result.cost);
+}
+
+#[test]
+#[should_panic(expected = "Row 1 has 2 columns, expected 3")]
+pub fn hungarian_ragged_test(){
+    hungarian(&[vec![1, 2, 3], vec![4, 5], vec![6, 7, 8]]);
+}
+
+#[test]
+pub fn hungarian_graph_test(){
+    // Jobs 0..=2 and workers 3..=6, a missing edge means the worker cannot take the job
+    let mut graph = Graph::new();
+    for _ in 0..7 {
+        graph.create((), 4);
+    }
+    let links = [(0, 3, 4), (0, 4, 1), (0, 4, 6), (1, 3, 2), (1, 5, 9), (2, 4, 3), (2, 6, 5)];
+    for (job, worker, cost) in links {
+        graph.edges.connect_weighted(job, worker, cost);
+    }
+    let jobs = [0, 1, 2];
+    let workers = [3, 4, 5, 6];
+    let assignment = hungarian_graph(&graph.edges, &jobs, &workers).unwrap();
+    assert_eq!(assignment.columns, vec![1, 0, 3]);
+    assert_eq!(assignment.cost, 8);
+
+    // Job 2 has no edge to either worker, and three jobs do not fit onto two workers
+    assert_eq!(hungarian_graph(&graph.edges, &[1, 2], &[3, 5]), None);
+    assert_eq!(hungarian_graph(&graph.edges, &jobs, &[3, 4]), None);
+}
@@ -1,14 +1,15 @@
 use std::cmp::min;
-use std::mem::size_of;
+use std::mem::{size_of, size_of_val};
+use std::rc::Rc;
 use std::time::{Instant};
 use crate::{graph};
 use crate::algorithms::{bfs, dfs};
 use crate::algorithms::ControlFlow::Resume;
-use crate::edge_storage::{HEADER_SIZE};
-use crate::graph::{Graph};
-use crate::handles::types::{VHandle, Weight};
-use crate::handles::{vh, wgt};
-use crate::traits::{EdgeOperator, EdgeStore, Transformer};
+use crate::edge_storage::{EdgeStorage, GrowthPolicy, HEADER_SIZE};
+use crate::graph::{Error, Graph};
+use crate::handles::types::{PackedEdge, VHandle, Weight};
+use crate::handles::{EdgePacking, vh, wgt};
+use crate::traits::{EdgeOperator, EdgeStore, IncomingEdgeStore, PayloadStore, Transformer, WeightedEdgeOperator};
 use crate::weighted_graph::WeightedGraph;
 
 #[test]
@@ -107,6 +108,7 @@ pub fn graph_with_capacity_test(){
 #[should_panic]
 pub fn graph_edge_overflow_test(){
     let mut graph = graph::Graph::with_reserve(3);
+    graph.edges.set_growth_policy(GrowthPolicy::Fixed);
     let count = 4;
     let a = graph.create_leaf(0);
 
@@ -115,6 +117,59 @@ pub fn graph_edge_overflow_test(){
     }
 }
 
+#[test]
+pub fn graph_edge_growth_test(){
+    let mut graph = graph::Graph::with_reserve(3);
+    let a = graph.create_leaf(0);
+    let b = graph.create_leaf(100);
+    graph.edges.connect(b, a);
+
+    let mut handles = Vec::new();
+    for i in 0..20 {
+        handles.push(graph.create_and_connect_leaf(a, i+1));
+    }
+
+    assert_eq!(graph.edges.len(a), 20);
+    assert!(graph.edges.edge_block_capacity(a) >= 20);
+    for (edge, handle) in graph.edges.edges(a).iter().zip(handles.iter()) {
+        assert_eq!(vh(*edge), *handle);
+    }
+
+    // Neighbouring blocks must not be touched by the relocation
+    assert_eq!(graph.edges.len(b), 1);
+    assert_eq!(vh(graph.edges.edges(b)[0]), a);
+}
+
+#[test]
+pub fn graph_edge_growth_factor_test(){
+    let mut graph = graph::Graph::with_reserve(2);
+    graph.edges.set_growth_policy(GrowthPolicy::Factor(3));
+    let a = graph.create_leaf(0);
+
+    for i in 0..3 {
+        graph.create_and_connect_leaf(a, i+1);
+    }
+    assert_eq!(graph.edges.edge_block_capacity(a), 6);
+}
+
+#[test]
+pub fn graph_edge_growth_reuse_test(){
+    let mut graph = graph::Graph::with_reserve(2);
+    let a = graph.create_leaf(0);
+    for i in 0..3 {
+        graph.create_and_connect_leaf(a, i+1);
+    }
+    let capacity = graph.edges.capacity();
+
+    // The block left behind by `a` fits a new vertex with the same reserve
+    let b = graph.create_leaf(10);
+    assert_eq!(graph.edges.capacity(), capacity);
+    assert_eq!(graph.edges.len(b), 0);
+    graph.edges.connect(b, a);
+    assert_eq!(vh(graph.edges.edges(b)[0]), a);
+    assert_eq!(graph.edges.len(a), 3);
+}
+
 
 #[test]
 pub fn graph_mutability_test(){
@@ -388,4 +443,404 @@ pub fn graph_weight_test(){
             _ => continue,
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+pub fn graph_remove_test(){
+    let mut graph = Graph::new_large();
+    let a = graph.create_leaf("a");
+    let b = graph.create_and_connect_leaf(a, "b");
+    let c = graph.create_and_connect_leaf(a, "c");
+    graph.edges.connect(b, c);
+    graph.edges.connect(c, b);
+    graph.edges.connect(c, c);
+
+    assert_eq!(graph.remove(c), "c");
+
+    assert!(!graph.is_alive(c));
+    assert_eq!(graph.vertices.count(), 2);
+    assert_eq!(graph.vertices.get(c), None);
+    assert_eq!(graph.edges.len(c), 0);
+    assert_eq!(graph.edges.len(a), 1);
+    assert_eq!(vh(graph.edges.edges(a)[0]), b);
+    assert_eq!(graph.edges.len(b), 0);
+}
+
+#[test]
+pub fn graph_remove_reuse_test(){
+    let mut graph = Graph::with_reserve(2);
+    let a = graph.create_leaf("a");
+    let b = graph.create_and_connect_leaf(a, "b");
+    let stale = graph.stable_handle(b);
+    assert_eq!(graph.resolve(stale), Some(b));
+
+    graph.remove(b);
+    assert_eq!(graph.resolve(stale), None);
+
+    let d = graph.create("d", 4);
+    assert_eq!(d, b);
+    assert_eq!(graph.vertices[d], "d");
+    assert_eq!(graph.edges.len(d), 0);
+    assert!(graph.edges.edge_block_capacity(d) >= 6);
+    assert_eq!(graph.resolve(stale), None);
+    assert_eq!(graph.resolve(graph.stable_handle(d)), Some(d));
+    assert_eq!(graph.edges.len(a), 0);
+}
+
+#[test]
+pub fn graph_remove_value_test(){
+    // Neither `Default` nor `Clone`, the value has to be handed back and dropped with the removal
+    struct Tracked(Rc<()>);
+    let counter = Rc::new(());
+    let mut graph = Graph::new();
+    let a = graph.create_leaf(Tracked(counter.clone()));
+    let b = graph.create_and_connect_leaf(a, Tracked(counter.clone()));
+    assert_eq!(Rc::strong_count(&counter), 3);
+
+    let removed = graph.remove(b);
+    assert!(Rc::ptr_eq(&removed.0, &counter));
+    drop(removed);
+    assert_eq!(Rc::strong_count(&counter), 2);
+    assert!(graph.vertices.get(b).is_none());
+    assert!(graph.vertices.get(a).is_some());
+
+    let c = graph.create_leaf(Tracked(counter.clone()));
+    assert_eq!(c, b);
+    assert_eq!(Rc::strong_count(&counter), 3);
+}
+
+#[test]
+pub fn graph_remove_incoming_test(){
+    let mut graph = Graph::new();
+    graph.edges.track_incoming();
+    let a = graph.create_leaf("a");
+    let b = graph.create_leaf("b");
+    let c = graph.create_leaf("c");
+    graph.edges.connect(a, c);
+    graph.edges.connect(a, b);
+    graph.edges.connect(a, c);
+    graph.edges.connect(b, c);
+    graph.edges.connect(c, c);
+    graph.edges.connect(c, a);
+
+    graph.remove(c);
+
+    assert_eq!(graph.edges.edges(a).iter().map(|edge| vh(*edge)).collect::<Vec<_>>(), vec![b]);
+    assert_eq!(graph.edges.len(b), 0);
+    assert_eq!(graph.edges.in_degree(a), 0);
+    assert_eq!(graph.edges.in_degree(b), 1);
+    assert_eq!(graph.edges.in_degree(c), 0);
+
+    let d = graph.create_leaf("d");
+    assert_eq!(d, c);
+    graph.edges.connect(b, d);
+    assert_eq!(graph.edges.in_degree(d), 1);
+}
+
+#[test]
+pub fn graph_transform_removed_test(){
+    let mut graph = Graph::new();
+    let a = graph.create_leaf(1);
+    let b = graph.create_leaf(2);
+    let c = graph.create_leaf(3);
+    graph.remove(b);
+
+    graph.vertices.transform(|slice| {
+        assert_eq!(slice.len(), 2);
+        for i in slice{
+            *i = *i * 10;
+        }
+    });
+    graph.vertices.async_transform(|slice| {
+        for i in slice{
+            *i = *i + 1;
+        }
+    });
+    assert_eq!(graph.vertices[a], 11);
+    assert_eq!(graph.vertices.get(b), None);
+    assert_eq!(graph.vertices[c], 31);
+}
+
+#[test]
+pub fn graph_disconnect_all_test(){
+    let mut graph = Graph::new();
+    graph.edges.track_incoming();
+    let a = graph.create_leaf("a");
+    let b = graph.create_leaf("b");
+    let c = graph.create_leaf("c");
+    graph.edges.connect(a, b);
+    graph.edges.connect(a, c);
+    graph.edges.connect(a, b);
+    graph.edges.connect(c, b);
+
+    graph.edges.disconnect_all(a, b);
+
+    assert_eq!(graph.edges.edges(a).iter().map(|edge| vh(*edge)).collect::<Vec<_>>(), vec![c]);
+    assert_eq!(graph.edges.in_edges(b).iter().map(|edge| vh(*edge)).collect::<Vec<_>>(), vec![c]);
+}
+
+#[test]
+#[should_panic]
+pub fn graph_remove_twice_test(){
+    let mut graph = Graph::new();
+    let a = graph.create_leaf(1);
+    graph.remove(a);
+    graph.remove(a);
+}
+
+#[test]
+pub fn graph_compact_test(){
+    let mut graph = Graph::new_large();
+    let a = graph.create_leaf("a");
+    let b = graph.create_and_connect_leaf(a, "b");
+    let c = graph.create_and_connect_leaf(a, "c");
+    graph.edges.connect(b, c);
+    graph.create_and_connect_leaf(a, "d");
+    graph.edges.disconnect(a, b);
+
+    let reclaimed = graph.edges.shrink_to_fit();
+    assert!(reclaimed > 0);
+    assert_eq!(graph.edges.capacity() as usize, graph.edges.packed_size(0));
+    assert_eq!(graph.edges.edge_block_capacity(a), 2);
+    assert_eq!(graph.edges.edge_block_capacity(c), 0);
+
+    let a_edges: Vec<VHandle> = graph.edges.edges(a).iter().map(|edge| vh(*edge)).collect();
+    assert_eq!(a_edges.len(), 2);
+    assert!(a_edges.contains(&c));
+    assert_eq!(graph.edges.edges(b).len(), 1);
+    assert_eq!(vh(graph.edges.edges(b)[0]), c);
+
+    // Blocks still grow after compaction
+    graph.edges.connect(c, a);
+    assert_eq!(vh(graph.edges.edges(c)[0]), a);
+}
+
+#[test]
+pub fn graph_compact_slack_test(){
+    let mut graph = Graph::with_reserve(10);
+    let a = graph.create_leaf(0);
+    for i in 0..3 {
+        graph.create_and_connect_leaf(a, i+1);
+    }
+    graph.remove(3);
+
+    graph.edges.compact(2);
+    assert_eq!(graph.edges.edge_block_capacity(a), 4);
+    assert_eq!(graph.edges.edge_block_capacity(1), 2);
+    assert_eq!(graph.edges.capacity(), (HEADER_SIZE + 2) * 4 + 2);
+}
+
+fn sources(edges: &[PackedEdge]) -> Vec<VHandle> {
+    let mut sources: Vec<VHandle> = edges.iter().map(|edge| vh(*edge)).collect();
+    sources.sort();
+    return sources;
+}
+
+#[test]
+pub fn graph_incoming_test(){
+    let mut wgraph = WeightedGraph::new_large();
+    let a = wgraph.graph.create_leaf("a");
+    let b = wgraph.graph.create_and_connect_leaf(a, "b");
+    wgraph.graph.edges.track_incoming();
+
+    let c = wgraph.create_and_connect_leaf_weighted(a, "c", 7);
+    wgraph.graph.edges.connect(b, c);
+    wgraph.graph.edges.connect_weighted(c, c, -3);
+
+    assert_eq!(wgraph.graph.edges.in_degree(a), 0);
+    assert_eq!(sources(wgraph.graph.edges.in_edges(b)), vec![a]);
+    assert_eq!(sources(wgraph.graph.edges.in_edges(c)), vec![a, b, c]);
+    for edge in wgraph.graph.edges.in_edges(c) {
+        match vh(*edge) {
+            0 => assert_eq!(wgt(*edge), 7),
+            2 => assert_eq!(wgt(*edge), -3),
+            _ => assert_eq!(wgt(*edge), 0),
+        }
+    }
+
+    wgraph.graph.edges.disconnect(a, c);
+    assert_eq!(sources(wgraph.graph.edges.in_edges(c)), vec![b, c]);
+
+    wgraph.graph.remove(b);
+    assert_eq!(sources(wgraph.graph.edges.in_edges(c)), vec![c]);
+    assert_eq!(wgraph.graph.edges.in_degree(b), 0);
+
+    let d = wgraph.graph.create_and_connect_leaf(c, "d");
+    assert_eq!(d, b);
+    assert_eq!(sources(wgraph.graph.edges.in_edges(d)), vec![c]);
+
+    wgraph.graph.edges.shrink_to_fit();
+    assert_eq!(sources(wgraph.graph.edges.in_edges(c)), vec![c]);
+    assert_eq!(sources(wgraph.graph.edges.in_edges(d)), vec![c]);
+}
+
+#[test]
+pub fn graph_incoming_bfs_test(){
+    let mut graph = Graph::new_large();
+    graph.edges.track_incoming();
+    let root = graph.create_leaf("root");
+    let a = graph.create_and_connect_leaf(root, "a");
+    let a_a = graph.create_and_connect_leaf(a, "a_a");
+    graph.create_and_connect_leaf(root, "b");
+
+    let mut visited = Vec::new();
+    let vertices_count = graph.vertices.len();
+    bfs(graph.edges.incoming_mut().unwrap(), a_a, vertices_count, |_edges, handle|{
+        visited.push(handle);
+        Resume
+    });
+    assert_eq!(visited, vec![a_a, a, root]);
+}
+
+#[test]
+pub fn graph_try_connect_test(){
+    let mut graph = Graph::with_reserve(1);
+    graph.edges.set_growth_policy(GrowthPolicy::Fixed);
+    let a = graph.try_create("a", 0).unwrap();
+    let b = graph.try_create("b", 0).unwrap();
+
+    assert_eq!(graph.try_connect(a, b), Ok(()));
+    assert_eq!(graph.try_connect(a, b), Err(Error::CapacityExceeded { vertex: a, capacity: 1 }));
+    assert_eq!(graph.try_connect(a, 42), Err(Error::InvalidHandle(42)));
+    assert_eq!(graph.try_connect(42, a), Err(Error::InvalidHandle(42)));
+    assert_eq!(graph.edges.len(a), 1);
+
+    assert_eq!(graph.try_disconnect(b, a), Err(Error::EdgeNotFound { src: b, target: a }));
+    assert_eq!(graph.try_disconnect(a, b), Ok(()));
+    assert_eq!(graph.try_edges(a).unwrap().len(), 0);
+
+    assert_eq!(graph.try_remove(b), Ok("b"));
+    assert_eq!(graph.try_remove(b), Err(Error::InvalidHandle(b)));
+    assert_eq!(graph.try_edges(b), Err(Error::InvalidHandle(b)));
+    assert_eq!(graph.try_get(b), Err(Error::InvalidHandle(b)));
+    assert_eq!(graph.try_connect(a, b), Err(Error::InvalidHandle(b)));
+    *graph.try_get_mut(a).unwrap() = "a_edited";
+    assert_eq!(graph.try_get(a), Ok(&"a_edited"));
+}
+
+#[test]
+pub fn edge_storage_try_connect_target_test(){
+    let mut edges: EdgeStorage = EdgeStorage::new();
+    let a = edges.extend_edge_storage(1);
+    assert_eq!(edges.try_connect(a, 5), Err(Error::InvalidHandle(5)));
+    assert_eq!(edges.try_connect_weighted(a, 5, 3), Err(Error::InvalidHandle(5)));
+    assert_eq!(edges.try_add_edges(a, &[PackedEdge::vh_pack(a), PackedEdge::vh_pack(9)]), Err(Error::InvalidHandle(9)));
+    assert_eq!(edges.len(a), 0);
+
+    // With the reverse index the failure must come before anything is written
+    edges.track_incoming();
+    assert_eq!(edges.try_connect(a, 5), Err(Error::InvalidHandle(5)));
+    assert_eq!(edges.len(a), 0);
+    assert_eq!(edges.in_degree(a), 0);
+    assert_eq!(edges.try_connect(a, a), Ok(()));
+    assert_eq!(edges.in_degree(a), 1);
+}
+
+#[test]
+pub fn graph_try_connect_weighted_test(){
+    let mut wgraph = WeightedGraph::with_reserve(1);
+    wgraph.graph.edges.set_growth_policy(GrowthPolicy::Fixed);
+    let a = wgraph.graph.create_leaf("a");
+    let b = wgraph.graph.create_leaf("b");
+
+    assert_eq!(wgraph.try_connect_weighted(a, b, -4), Ok(()));
+    assert!(wgraph.try_connect_weighted(a, b, 4).is_err());
+    assert_eq!(wgraph.try_connect_weighted(a, 7, 4), Err(Error::InvalidHandle(7)));
+    assert_eq!(wgt(wgraph.graph.edges.edges(a)[0]), -4);
+}
+
+#[test]
+pub fn graph_mixed_width_test(){
+    let mut small = Graph::with_storage(EdgeStorage::<u16>::with_reserve(2));
+    let mut large = WeightedGraph::with_storage(EdgeStorage::<u64>::new());
+
+    let small_root = small.create_leaf("small_root");
+    let small_child: u8 = small.create_and_connect_leaf(small_root, "small_child");
+    let large_root = large.graph.create_leaf("large_root");
+    let large_child: u32 = large.create_and_connect_leaf_weighted(large_root, "large_child", -100_000);
+
+    assert_eq!(small.edges.edges(small_root)[0].vh(), small_child);
+    assert_eq!(small.vertices[small_child], "small_child");
+    assert_eq!(large.graph.edges.edges(large_root)[0].vh(), large_child);
+    assert_eq!(large.graph.edges.edges(large_root)[0].wgt(), -100_000);
+    assert_eq!(size_of::<u16>(), size_of_val(&small.edges.edges[0]));
+
+    let mut visited = Vec::new();
+    bfs(&mut small.edges, small_root, small.vertices.len(), |_edges, handle|{
+        visited.push(handle);
+        Resume
+    });
+    assert_eq!(visited, vec![small_root, small_child]);
+}
+
+#[test]
+pub fn graph_small_handle_space_test(){
+    let mut graph = Graph::with_storage(EdgeStorage::<u16>::new());
+    for i in 0..u8::MAX {
+        assert_eq!(graph.try_create(i, 0), Ok(i));
+    }
+    assert_eq!(graph.try_create(0, 0), Err(Error::HandleSpaceExhausted));
+    graph.remove(7);
+    assert_eq!(graph.try_create(0, 0), Ok(7));
+}
+
+#[test]
+pub fn graph_block_header_limit_test(){
+    // Length and capacity of a u16 block live in u16 header slots, so it holds at most u16::MAX edges
+    let mut graph = Graph::with_storage(EdgeStorage::<u16>::new());
+    let a = graph.create_leaf("a");
+    let b = graph.create_leaf("b");
+    for _ in 0..u16::MAX {
+        graph.edges.connect(a, b);
+    }
+    assert_eq!(graph.edges.len(a), u16::MAX as usize);
+    assert_eq!(graph.edges.edge_block_capacity(a), u16::MAX as usize);
+    assert_eq!(graph.edges.try_connect(a, b), Err(Error::CapacityExceeded { vertex: a, capacity: u16::MAX as usize }));
+    assert_eq!(graph.edges.len(a), u16::MAX as usize);
+    assert!(graph.edges.edges(a).iter().all(|edge| edge.vh() == b));
+
+    graph.edges.connect(b, a);
+    graph.edges.compact(10);
+    assert_eq!(graph.edges.edge_block_capacity(a), u16::MAX as usize);
+    assert_eq!(graph.edges.edge_block_capacity(b), 11);
+    assert_eq!(graph.edges.capacity(), graph.edges.packed_size(10));
+}
+
+#[test]
+pub fn graph_payload_test(){
+    let mut graph = Graph::with_storage(EdgeStorage::<u64, String>::with_reserve(1));
+    let a = graph.create_leaf("a");
+    let b = graph.create_leaf("b");
+    let c = graph.create_leaf("c");
+    let d = graph.create_leaf("d");
+
+    graph.edges.connect_with(a, b, "a->b".to_string());
+    graph.edges.connect_with(a, c, "a->c".to_string()); // Relocates the block
+    graph.edges.connect(a, d);
+    graph.edges.connect_with(b, c, "b->c".to_string());
+
+    let a_edges: Vec<(u32, &String)> = graph.edges.edges_with_payload(a).collect();
+    assert_eq!(a_edges, vec![(b, &"a->b".to_string()), (c, &"a->c".to_string()), (d, &String::new())]);
+
+    graph.edges.disconnect(a, b);
+    assert_eq!(graph.edges.payload(a, b), None);
+    assert_eq!(graph.edges.payload(a, c), Some(&"a->c".to_string()));
+    assert_eq!(graph.edges.payload(a, d), Some(&String::new()));
+
+    *graph.edges.payload_mut(a, d).unwrap() = "a->d".to_string();
+    graph.edges.shrink_to_fit();
+    assert_eq!(graph.edges.payload(a, d), Some(&"a->d".to_string()));
+    assert_eq!(graph.edges.payload(b, c), Some(&"b->c".to_string()));
+
+    graph.remove(c);
+    assert_eq!(graph.edges.payloads(a), &["a->d".to_string()]);
+    assert_eq!(graph.edges.payloads(b).len(), 0);
+}
+
+#[test]
+pub fn graph_payload_zero_overhead_test(){
+    let mut graph = Graph::new_large();
+    let a = graph.create_leaf(0);
+    graph.create_and_connect_leaf(a, 1);
+    assert_eq!(size_of_val(&graph.edges.payloads[..]), 0);
+    assert_eq!(graph.edges.payloads(a).len(), 1);
+}
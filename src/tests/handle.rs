@@ -1,4 +1,4 @@
-use crate::handles::{pack, set_vid, set_wgt, vh, wgt};
+use crate::handles::{EdgePacking, pack, set_vid, set_wgt, vh, wgt};
 use crate::handles::types::{VHandle, Weight};
 
 #[test]
@@ -129,4 +129,24 @@ pub fn combined_set_test(){
 
     assert_eq!(vh(handle), VHandle::MAX);
     assert_eq!(wgt(handle), -Weight::MAX);
-}
\ No newline at end of file
+}
+#[test]
+pub fn packing_widths_test(){
+    let handle = u16::pack(200, -7);
+    assert_eq!(handle.vh(), 200u8);
+    assert_eq!(handle.wgt(), -7i8);
+    assert_eq!(handle.set_vid(3).vh(), 3);
+    assert_eq!(handle.set_vid(3).wgt(), -7);
+
+    let handle = u32::pack(u16::MAX, i16::MAX);
+    assert_eq!(handle.vh(), u16::MAX);
+    assert_eq!(handle.wgt(), i16::MAX);
+    assert_eq!(handle.set_wgt(-300).wgt(), -300);
+    assert_eq!(handle.set_wgt(-300).vh(), u16::MAX);
+
+    let handle = u64::pack(1231, -8997);
+    assert_eq!(handle.vh(), 1231u32);
+    assert_eq!(handle.wgt(), -8997i32);
+    assert_eq!(u64::vh_pack(5).vh(), 5);
+    assert_eq!(u64::vh_pack(5).wgt(), 0);
+}
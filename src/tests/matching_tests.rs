@@ -0,0 +1,244 @@
+use crate::algorithms::{bipartition, hopcroft_karp, maximum_matching, maximum_weight_matching, Matching};
+use crate::edge_storage::EdgeStorage;
+use crate::handles::types::VHandle;
+use crate::traits::EdgeOperator;
+use crate::undirected_graph::UndirectedGraph;
+
+fn build_undirected(vertices_count: usize, links: &[(VHandle, VHandle)]) -> UndirectedGraph<(), EdgeStorage> {
+    let mut graph = UndirectedGraph::new();
+    for _ in 0..vertices_count {
+        graph.graph.create((), 4);
+    }
+    for (a, b) in links {
+        graph.connect(*a, *b);
+    }
+    return graph;
+}
+
+/// Every pair is an edge and no vertex is used twice.
+fn assert_valid_matching(links: &[(VHandle, VHandle)], matching: &Matching<VHandle>) {
+    let pairs = matching.pairs();
+    assert_eq!(pairs.len(), matching.size);
+    for (a, b) in &pairs {
+        assert!(links.contains(&(*a, *b)) || links.contains(&(*b, *a)));
+        assert_eq!(matching.mate(*a), Some(*b));
+        assert_eq!(matching.mate(*b), Some(*a));
+    }
+}
+
+#[test]
+pub fn bipartition_test(){
+    let links = [(0, 1), (1, 2), (2, 3), (3, 0), (4, 5)];
+    let graph = build_undirected(6, &links);
+    let partition = bipartition(&graph.graph.edges, 6).unwrap();
+    assert_eq!(partition.left::<VHandle>(), vec![0, 2, 4]);
+    assert_eq!(partition.right::<VHandle>(), vec![1, 3, 5]);
+    for (a, b) in links {
+        assert_ne!(partition.is_right(a), partition.is_right(b));
+    }
+}
+
+#[test]
+pub fn bipartition_directed_test(){
+    // Directions are ignored, 2 -> 0 still closes a triangle
+    let mut graph: UndirectedGraph<(), EdgeStorage> = UndirectedGraph::new();
+    for _ in 0..3 {
+        graph.graph.create((), 2);
+    }
+    graph.graph.edges.connect(0, 1);
+    graph.graph.edges.connect(1, 2);
+    graph.graph.edges.connect(2, 0);
+    let cycle = bipartition(&graph.graph.edges, 3).unwrap_err();
+    assert_eq!(cycle.vertices.len(), 3);
+}
+
+#[test]
+pub fn odd_cycle_witness_test(){
+    let links = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 0), (0, 5)];
+    let graph = build_undirected(6, &links);
+    let cycle = bipartition(&graph.graph.edges, 6).unwrap_err();
+    assert_eq!(cycle.vertices.len() % 2, 1);
+    let mut sorted = cycle.vertices.clone();
+    sorted.sort();
+    assert_eq!(sorted, vec![0, 1, 2, 3, 4]);
+    for (index, a) in cycle.vertices.iter().enumerate() {
+        let b = cycle.vertices[(index + 1) % cycle.vertices.len()];
+        assert!(graph.is_connected(*a, b));
+    }
+
+    let self_loop = build_undirected(2, &[(0, 1), (1, 1)]);
+    assert_eq!(bipartition(&self_loop.graph.edges, 2).unwrap_err().vertices, vec![1]);
+}
+
+#[test]
+pub fn hopcroft_karp_test(){
+    // Left 0..4, right 4..8, maximum matching has size 3 since 0 and 1 only reach 4
+    let links = [(0, 4), (1, 4), (2, 4), (2, 5), (3, 5), (3, 6), (3, 7)];
+    let graph = build_undirected(8, &links);
+    let result = hopcroft_karp(&graph.graph.edges, 8).unwrap();
+    assert_valid_matching(&links, &result.matching);
+    assert_eq!(result.matching.size, 3);
+
+    // König: the cover is as large as the matching and touches every edge
+    assert_eq!(result.vertex_cover.len(), result.matching.size);
+    for (a, b) in links {
+        assert!(result.vertex_cover.contains(&a) || result.vertex_cover.contains(&b));
+    }
+}
+
+#[test]
+pub fn hopcroft_karp_perfect_test(){
+    // Greedy matching 0-3, 1-4 blocks 2, augmenting paths have to fix it
+    let links = [(0, 3), (0, 4), (1, 4), (1, 5), (2, 3)];
+    let graph = build_undirected(6, &links);
+    let result = hopcroft_karp(&graph.graph.edges, 6).unwrap();
+    assert_valid_matching(&links, &result.matching);
+    assert_eq!(result.matching.size, 3);
+    assert_eq!(result.matching.pairs(), vec![(0, 4), (1, 5), (2, 3)]);
+    assert_eq!(result.vertex_cover.len(), 3);
+}
+
+#[test]
+pub fn hopcroft_karp_grid_test(){
+    let side = 12;
+    let mut links = Vec::new();
+    for row in 0..side {
+        for column in 0..side {
+            let vertex = (row * side + column) as VHandle;
+            if column + 1 < side {
+                links.push((vertex, vertex + 1));
+            }
+            if row + 1 < side {
+                links.push((vertex, vertex + side as VHandle));
+            }
+        }
+    }
+    let graph = build_undirected(side * side, &links);
+    let result = hopcroft_karp(&graph.graph.edges, side * side).unwrap();
+    assert_valid_matching(&links, &result.matching);
+    assert_eq!(result.matching.size, side * side / 2);
+    assert_eq!(result.vertex_cover.len(), side * side / 2);
+    for (a, b) in &links {
+        assert!(result.vertex_cover.contains(a) || result.vertex_cover.contains(b));
+    }
+}
+
+#[test]
+pub fn hopcroft_karp_not_bipartite_test(){
+    let graph = build_undirected(3, &[(0, 1), (1, 2), (2, 0)]);
+    assert_eq!(hopcroft_karp(&graph.graph.edges, 3).unwrap_err().vertices.len(), 3);
+}
+
+fn build_weighted(vertices_count: usize, links: &[(VHandle, VHandle, i32)]) -> UndirectedGraph<(), EdgeStorage> {
+    let mut graph = UndirectedGraph::new();
+    for _ in 0..vertices_count {
+        graph.graph.create((), vertices_count);
+    }
+    for (a, b, weight) in links {
+        graph.connect_weighted(*a, *b, *weight);
+    }
+    return graph;
+}
+
+/// Best (size, weight) over all matchings, by size first when `by_size` is set.
+fn brute_force_matching(vertices_count: usize, links: &[(VHandle, VHandle, i32)], by_size: bool) -> (usize, i64) {
+    fn best(vertex: usize, used: u32, vertices_count: usize, links: &[(VHandle, VHandle, i32)], by_size: bool) -> (usize, i64) {
+        if vertex == vertices_count {
+            return (0, 0);
+        }
+        if used & (1 << vertex) != 0 {
+            return best(vertex + 1, used, vertices_count, links, by_size);
+        }
+        let mut result = best(vertex + 1, used | (1 << vertex), vertices_count, links, by_size);
+        for (a, b, weight) in links {
+            let other = if *a as usize == vertex { *b as usize } else if *b as usize == vertex { *a as usize } else { continue };
+            if other == vertex || used & (1 << other) != 0 {
+                continue;
+            }
+            let (size, total) = best(vertex + 1, used | (1 << vertex) | (1 << other), vertices_count, links, by_size);
+            let candidate = (size + 1, total + *weight as i64);
+            let better = if by_size { candidate > result } else { candidate.1 > result.1 };
+            if better {
+                result = candidate;
+            }
+        }
+        return result;
+    }
+    return best(0, 0, vertices_count, links, by_size);
+}
+
+#[test]
+pub fn blossom_test(){
+    // Two triangles joined by a path, greedy picks 1-2 and 4-5 leaving 0 and 6 stranded
+    let links = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 6), (6, 4), (0, 7)];
+    let graph = build_undirected(8, &links);
+    let matching = maximum_matching(&graph.graph.edges, 8);
+    assert_valid_matching(&links, &matching);
+    assert_eq!(matching.size, 4);
+
+    // The Petersen graph has a perfect matching
+    let petersen = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 0), (0, 5), (1, 6), (2, 7), (3, 8), (4, 9), (5, 7), (7, 9), (9, 6), (6, 8), (8, 5)];
+    let graph = build_undirected(10, &petersen);
+    let matching = maximum_matching(&graph.graph.edges, 10);
+    assert_valid_matching(&petersen, &matching);
+    assert_eq!(matching.size, 5);
+
+    let odd = build_undirected(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0), (2, 2)]);
+    assert_eq!(maximum_matching(&odd.graph.edges, 5).size, 2);
+}
+
+#[test]
+pub fn weighted_matching_test(){
+    // The heaviest edge alone beats the two lighter ones around it, unless the size matters more
+    let links = [(0, 1, 5), (1, 2, 11), (2, 3, 5)];
+    let graph = build_weighted(4, &links);
+    let heaviest = maximum_weight_matching(&graph.graph.edges, 4, false);
+    assert_eq!(heaviest.weight, 11);
+    assert_eq!(heaviest.matching.pairs(), vec![(1, 2)]);
+    let largest = maximum_weight_matching(&graph.graph.edges, 4, true);
+    assert_eq!(largest.weight, 10);
+    assert_eq!(largest.matching.pairs(), vec![(0, 1), (2, 3)]);
+
+    // Negative edges are only taken when the size demands it
+    let negative = build_weighted(4, &[(0, 1, 2), (1, 2, -1), (2, 3, -3), (0, 3, 1)]);
+    assert_eq!(maximum_weight_matching(&negative.graph.edges, 4, false).weight, 2);
+    assert_eq!(maximum_weight_matching(&negative.graph.edges, 4, true).weight, 0);
+}
+
+#[test]
+pub fn matching_random_test(){
+    let mut seed: u64 = 11;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        return (seed >> 33) as usize;
+    };
+    for round in 0..300 {
+        let vertices_count = 2 + next() % 9;
+        let links_count = next() % (2 * vertices_count + 1);
+        let mut links = Vec::new();
+        for _ in 0..links_count {
+            let a = (next() % vertices_count) as VHandle;
+            let b = (next() % vertices_count) as VHandle;
+            if a == b || links.iter().any(|(x, y, _)| (*x, *y) == (a, b) || (*x, *y) == (b, a)) {
+                continue;
+            }
+            // Small weight ranges produce many ties, which stress blossom expansion
+            let weight = if round % 2 == 0 { (next() % 6) as i32 } else { (next() % 40) as i32 - 8 };
+            links.push((a, b, weight));
+        }
+        let graph = build_weighted(vertices_count, &links);
+        let unweighted: Vec<(VHandle, VHandle)> = links.iter().map(|(a, b, _)| (*a, *b)).collect();
+
+        let matching = maximum_matching(&graph.graph.edges, vertices_count);
+        assert_valid_matching(&unweighted, &matching);
+        assert_eq!(matching.size, brute_force_matching(vertices_count, &links, true).0);
+
+        let heaviest = maximum_weight_matching(&graph.graph.edges, vertices_count, false);
+        assert_valid_matching(&unweighted, &heaviest.matching);
+        assert_eq!(heaviest.weight, brute_force_matching(vertices_count, &links, false).1, "round {}", round);
+
+        let largest = maximum_weight_matching(&graph.graph.edges, vertices_count, true);
+        assert_valid_matching(&unweighted, &largest.matching);
+        assert_eq!((largest.matching.size, largest.weight), brute_force_matching(vertices_count, &links, true), "round {}", round);
+    }
+}
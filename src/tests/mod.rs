@@ -1,3 +1,57 @@
+use crate::edge_storage::EdgeStorage;
+use crate::graph::Graph;
+use crate::handles::types::VHandle;
+use crate::traits::EdgeOperator;
+
 pub mod graph_tests;
 mod tree_tests;
 mod handle;
+mod csr_tests;
+mod undirected_tests;
+mod traversal_tests;
+mod visited_tests;
+mod epoch_tests;
+mod shortest_path_tests;
+mod topological_tests;
+mod components_tests;
+mod spanning_tree_tests;
+mod flow_tests;
+mod matching_tests;
+mod pagerank_tests;
+
+/// The tree shared by the traversal tests together with its root.
+pub(in crate) struct TreeGraph {
+    pub graph: Graph<&'static str, EdgeStorage>,
+    pub root: VHandle,
+}
+
+/// root -> {a, b, c}, a -> {a_a, a_b}, b -> b_a -> b_a_a.
+pub(in crate) fn build_tree_graph() -> TreeGraph {
+    return build_tree(false);
+}
+
+/// The tree of `build_tree_graph` with the back edges b -> root, b -> a and a_b -> root, which traversals
+/// must not follow to already visited vertices.
+pub(in crate) fn build_cyclic_tree_graph() -> TreeGraph {
+    return build_tree(true);
+}
+
+fn build_tree(back_edges: bool) -> TreeGraph {
+    let mut graph = Graph::new_large();
+    let root = graph.create_leaf("root");
+    let a = graph.create_and_connect_leaf(root, "a");
+    let b = graph.create_and_connect_leaf(root, "b");
+    graph.create_and_connect_leaf(root, "c");
+
+    graph.create_and_connect_leaf(a, "a_a");
+    let a_b = graph.create_and_connect_leaf(a, "a_b");
+
+    let b_a = graph.create_and_connect_leaf(b, "b_a");
+    graph.create_and_connect_leaf(b_a, "b_a_a");
+    if back_edges {
+        graph.edges.connect(b, root);
+        graph.edges.connect(b, a);
+        graph.edges.connect(a_b, root);
+    }
+    return TreeGraph{graph, root};
+}
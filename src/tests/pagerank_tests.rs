@@ -0,0 +1,168 @@
+use crate::algorithms::{pagerank, pagerank_with, personalized_pagerank, PageRankSettings};
+use crate::edge_storage::EdgeStorage;
+use crate::handles::types::VHandle;
+use crate::traits::WeightedEdgeOperator;
+use crate::weighted_graph::WeightedGraph;
+
+fn build_graph(vertices_count: usize, links: &[(VHandle, VHandle, i32)]) -> WeightedGraph<(), EdgeStorage> {
+    let mut graph = WeightedGraph::new();
+    for _ in 0..vertices_count {
+        graph.graph.create((), 4);
+    }
+    for (src, target, weight) in links {
+        graph.graph.edges.connect_weighted(*src, *target, *weight);
+    }
+    return graph;
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!((actual - expected).abs() < tolerance, "{} is not close to {}", actual, expected);
+}
+
+#[test]
+pub fn pagerank_test(){
+    // A -> B, A -> C, B -> C, C -> A, D -> C
+    let graph = build_graph(4, &[(0, 1, 1), (0, 2, 1), (1, 2, 1), (2, 0, 1), (3, 2, 1)]);
+    let result = pagerank(&graph.graph.edges, 4, 0.85, 1e-10, 100);
+    assert!(result.converged);
+    assert_close(result.ranks.iter().sum(), 1.0, 1e-9);
+    assert_close(result.rank(0 as VHandle), 0.3725, 1e-3);
+    assert_close(result.rank(1 as VHandle), 0.1958, 1e-3);
+    assert_close(result.rank(2 as VHandle), 0.3941, 1e-3);
+    assert_close(result.rank(3 as VHandle), 0.0375, 1e-3);
+    assert_eq!(result.order::<VHandle>(), vec![2, 0, 1, 3]);
+
+    let cycle = build_graph(5, &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 4, 1), (4, 0, 1)]);
+    let result = pagerank(&cycle.graph.edges, 5, 0.85, 1e-12, 100);
+    for rank in result.ranks {
+        assert_close(rank, 0.2, 1e-9);
+    }
+
+    let empty = build_graph(0, &[]);
+    assert!(pagerank(&empty.graph.edges, 0, 0.85, 1e-9, 10).ranks.is_empty());
+}
+
+#[test]
+pub fn pagerank_dangling_test(){
+    // 2 and 3 have no outgoing edges, their rank is spread over everyone instead of getting lost
+    let graph = build_graph(4, &[(0, 1, 1), (1, 2, 1), (1, 3, 1)]);
+    let result = pagerank(&graph.graph.edges, 4, 0.85, 1e-12, 200);
+    assert!(result.converged);
+    assert_close(result.ranks.iter().sum(), 1.0, 1e-9);
+    assert_close(result.rank(2 as VHandle), result.rank(3 as VHandle), 1e-12);
+    assert!(result.rank(2 as VHandle) > result.rank(0 as VHandle));
+
+    let limited = pagerank(&graph.graph.edges, 4, 0.85, 1e-15, 3);
+    assert!(!limited.converged);
+    assert_eq!(limited.iterations, 3);
+}
+
+#[test]
+pub fn weighted_pagerank_test(){
+    let links = [(0, 1, 3), (0, 2, 1), (1, 0, 1), (2, 0, 1), (2, 3, 0), (3, 0, 1)];
+    let graph = build_graph(4, &links);
+    let even = pagerank(&graph.graph.edges, 4, 0.85, 1e-12, 200);
+    let mut settings = PageRankSettings::new(0.85, 1e-12, 200);
+    settings.weighted = true;
+    let weighted = pagerank_with(&graph.graph.edges, 4, &settings);
+    assert!(weighted.converged);
+    assert_close(weighted.ranks.iter().sum(), 1.0, 1e-9);
+    assert_close(even.rank(1 as VHandle), even.rank(2 as VHandle), 1e-12);
+    assert!(weighted.rank(1 as VHandle) > 2.0 * weighted.rank(2 as VHandle));
+    // The zero weight edge is never followed, so 3 only gets the restart share
+    assert!(even.rank(3 as VHandle) > weighted.rank(3 as VHandle));
+    assert_close(weighted.rank(3 as VHandle), 0.15 / 4.0, 1e-9);
+}
+
+#[test]
+pub fn personalized_pagerank_test(){
+    // Chain 0 -> 1 -> 2 -> 3 and a separate pair 4 <-> 5
+    let graph = build_graph(6, &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 0, 1), (4, 5, 1), (5, 4, 1)]);
+    let restart = [2.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+    let result = personalized_pagerank(&graph.graph.edges, 6, &restart, 0.85, 1e-12, 500);
+    assert!(result.converged);
+    assert_close(result.ranks.iter().sum(), 1.0, 1e-9);
+    assert_eq!(result.order::<VHandle>(), vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(result.rank(4 as VHandle), 0.0);
+    assert_eq!(result.rank(5 as VHandle), 0.0);
+    // Restart mass 0.15 on vertex 0 and a cycle of length 4: r0 = 0.15 / (1 - 0.85^4)
+    assert_close(result.rank(0 as VHandle), 0.15 / (1.0 - 0.85f64.powi(4)), 1e-9);
+}
+
+#[test]
+#[should_panic]
+pub fn personalized_pagerank_invalid_restart_test(){
+    let graph = build_graph(2, &[(0, 1, 1)]);
+    personalized_pagerank(&graph.graph.edges, 2, &[0.0, 0.0], 0.85, 1e-9, 10);
+}
+
+#[test]
+pub fn parallel_pagerank_test(){
+    let vertices_count = 3001;
+    let mut seed: u64 = 5;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        return (seed >> 33) as usize;
+    };
+    let mut graph: WeightedGraph<(), EdgeStorage> = WeightedGraph::new();
+    for _ in 0..vertices_count {
+        graph.graph.create((), 4);
+    }
+    for src in 0..vertices_count {
+        // Every seventh vertex is dangling
+        if src % 7 == 0 {
+            continue;
+        }
+        for _ in 0..1 + next() % 5 {
+            graph.graph.edges.connect_weighted(src as VHandle, (next() % vertices_count) as VHandle, (next() % 10) as i32);
+        }
+    }
+
+    for weighted in [false, true] {
+        let mut settings = PageRankSettings::new(0.85, 1e-10, 100);
+        settings.weighted = weighted;
+        settings.restart = Some((0..vertices_count).map(|vertex| (vertex % 3) as f64).collect());
+        let serial = pagerank_with(&graph.graph.edges, vertices_count, &settings);
+        settings.parallel = true;
+        let parallel = pagerank_with(&graph.graph.edges, vertices_count, &settings);
+        assert!(serial.converged);
+        assert_eq!(serial.iterations, parallel.iterations);
+        assert_close(parallel.ranks.iter().sum(), 1.0, 1e-9);
+        for (a, b) in serial.ranks.iter().zip(&parallel.ranks) {
+            assert_close(*a, *b, 1e-12);
+        }
+    }
+}
+
+#[test]
+pub fn pagerank_removed_vertex_test(){
+    // Same graph as pagerank_test with a fifth vertex wired into it, leaving it out must give the same ranks
+    let mut graph = build_graph(5, &[(0, 1, 1), (0, 2, 1), (1, 2, 1), (2, 0, 1), (3, 2, 1), (4, 0, 1), (1, 4, 1), (4, 4, 1)]);
+    let mut settings = PageRankSettings::new(0.85, 1e-10, 100);
+    settings.alive = Some(vec![true, true, true, true, false]);
+    let result = pagerank_with(&graph.graph.edges, 5, &settings);
+    assert!(result.converged);
+    assert_close(result.ranks.iter().sum(), 1.0, 1e-9);
+    assert_close(result.rank(0 as VHandle), 0.3725, 1e-3);
+    assert_close(result.rank(1 as VHandle), 0.1958, 1e-3);
+    assert_close(result.rank(2 as VHandle), 0.3941, 1e-3);
+    assert_close(result.rank(3 as VHandle), 0.0375, 1e-3);
+    assert_eq!(result.rank(4 as VHandle), 0.0);
+
+    settings.restart = Some(vec![1.0, 0.0, 0.0, 0.0, 5.0]);
+    let personalized = pagerank_with(&graph.graph.edges, 5, &settings);
+    assert_close(personalized.ranks.iter().sum(), 1.0, 1e-9);
+    assert_eq!(personalized.rank(4 as VHandle), 0.0);
+    assert_eq!(personalized.rank(3 as VHandle), 0.0);
+
+    graph.graph.remove(4 as VHandle);
+    settings.restart = None;
+    settings.alive = Some((0..5).map(|vertex| graph.graph.is_alive(vertex as VHandle)).collect());
+    let removed = pagerank_with(&graph.graph.edges, 5, &settings);
+    for (a, b) in result.ranks.iter().zip(&removed.ranks) {
+        assert_close(*a, *b, 1e-12);
+    }
+
+    settings.alive = Some(vec![false; 5]);
+    assert_eq!(pagerank_with(&graph.graph.edges, 5, &settings).ranks, vec![0.0; 5]);
+}
@@ -0,0 +1,312 @@
+use crate::algorithms::{astar, astar_with, bellman_ford, dijkstra, dijkstra_to, floyd_warshall, johnson, NegativeCycle, Path, SearchOutcome};
+use crate::algorithms::ControlFlow::{End, Resume};
+use crate::edge_storage::EdgeStorage;
+use crate::handles::types::VHandle;
+use crate::handles::{vh, wgt};
+use crate::traits::EdgeStore;
+use crate::traits::WeightedEdgeOperator;
+use crate::weighted_graph::WeightedGraph;
+
+/// s -7-> a, s -2-> b, b -3-> a, a -1-> c, b -8-> c, c -2-> d, e is unreachable.
+fn build_graph() -> WeightedGraph<&'static str, EdgeStorage> {
+    let mut graph = WeightedGraph::new();
+    for name in ["s", "a", "b", "c", "d", "e"] {
+        graph.graph.create(name, 2);
+    }
+    let edges = &mut graph.graph.edges;
+    edges.connect_weighted(0, 1, 7);
+    edges.connect_weighted(0, 2, 2);
+    edges.connect_weighted(2, 1, 3);
+    edges.connect_weighted(1, 3, 1);
+    edges.connect_weighted(2, 3, 8);
+    edges.connect_weighted(3, 4, 2);
+    edges.connect_weighted(4, 0, 1);
+    return graph;
+}
+
+#[test]
+pub fn dijkstra_test(){
+    let graph = build_graph();
+    let paths = dijkstra(&graph.graph.edges, 0, graph.graph.vertices.len());
+    assert_eq!(paths.distances, vec![Some(0), Some(5), Some(2), Some(6), Some(8), None]);
+    assert_eq!(paths.predecessor(0), None);
+    assert_eq!(paths.predecessor(1), Some(2));
+    assert!(!paths.is_reachable(5));
+    assert_eq!(paths.path_to(4), Some(Path{vertices: vec![0, 2, 1, 3, 4], cost: 8}));
+    assert_eq!(paths.path_to(0), Some(Path{vertices: vec![0], cost: 0}));
+    assert_eq!(paths.path_to(5), None);
+}
+
+#[test]
+pub fn dijkstra_to_test(){
+    let graph = build_graph();
+    let vertices_count = graph.graph.vertices.len();
+    assert_eq!(dijkstra_to(&graph.graph.edges, 0, 3, vertices_count), Some(Path{vertices: vec![0, 2, 1, 3], cost: 6}));
+    assert_eq!(dijkstra_to(&graph.graph.edges, 3, 1, vertices_count), Some(Path{vertices: vec![3, 4, 0, 2, 1], cost: 8}));
+    assert_eq!(dijkstra_to(&graph.graph.edges, 0, 5, vertices_count), None);
+}
+
+#[test]
+pub fn dijkstra_grid_test(){
+    // 20x20 grid with unit weights to the right and down, distance is the manhattan distance
+    let size = 20;
+    let mut graph: WeightedGraph<(usize, usize), EdgeStorage> = WeightedGraph::new();
+    for y in 0..size {
+        for x in 0..size {
+            graph.graph.create((x, y), 2);
+        }
+    }
+    for y in 0..size {
+        for x in 0..size {
+            let vertex = (y * size + x) as VHandle;
+            if x + 1 < size {
+                graph.graph.edges.connect_weighted(vertex, vertex + 1, 1);
+            }
+            if y + 1 < size {
+                graph.graph.edges.connect_weighted(vertex, vertex + size as VHandle, 1);
+            }
+        }
+    }
+    let paths = dijkstra(&graph.graph.edges, 0, graph.graph.vertices.len());
+    for vertex in 0..graph.graph.vertices.len() as VHandle {
+        let (x, y) = graph.graph.vertices[vertex];
+        assert_eq!(paths.distance(vertex), Some((x + y) as i64));
+        assert_eq!(paths.path_to(vertex).unwrap().vertices.len(), x + y + 1);
+    }
+}
+
+#[test]
+pub fn dijkstra_narrow_weights_test(){
+    // Costs are summed in i64 so long paths of i8 weights do not overflow
+    let mut graph: WeightedGraph<usize, EdgeStorage<u16>> = WeightedGraph::with_storage(EdgeStorage::<u16>::new());
+    let mut last = graph.graph.create(0, 1);
+    for i in 1..100 {
+        last = graph.create_and_connect_weighted(last, i, 100, 1);
+    }
+    let paths = dijkstra(&graph.graph.edges, 0, graph.graph.vertices.len());
+    assert_eq!(paths.distance(last), Some(9900));
+}
+
+#[test]
+pub fn bellman_ford_test(){
+    let graph = build_graph();
+    let vertices_count = graph.graph.vertices.len();
+    let paths = bellman_ford(&graph.graph.edges, 0, vertices_count).unwrap();
+    assert_eq!(paths, dijkstra(&graph.graph.edges, 0, vertices_count));
+
+    // s -4-> a -(-3)-> b -(-2)-> c, s -1-> c
+    let mut graph: WeightedGraph<&str, EdgeStorage> = WeightedGraph::new();
+    let s = graph.graph.create("s", 2);
+    let a = graph.create_and_connect_weighted(s, "a", 4, 1);
+    let b = graph.create_and_connect_weighted(a, "b", -3, 1);
+    let c = graph.create_and_connect_weighted(b, "c", -2, 1);
+    graph.graph.edges.connect_weighted(s, c, 1);
+    let paths = bellman_ford(&graph.graph.edges, s, graph.graph.vertices.len()).unwrap();
+    assert_eq!(paths.distances, vec![Some(0), Some(4), Some(1), Some(-1)]);
+    assert_eq!(paths.path_to(c), Some(Path{vertices: vec![s, a, b, c], cost: -1}));
+}
+
+#[test]
+pub fn bellman_ford_negative_cycle_test(){
+    // s -> a -> b -> c -> a with the a, b, c cycle summing to -1
+    let mut graph: WeightedGraph<&str, EdgeStorage> = WeightedGraph::new();
+    let s = graph.graph.create("s", 1);
+    let a = graph.create_and_connect_weighted(s, "a", 5, 1);
+    let b = graph.create_and_connect_weighted(a, "b", 2, 1);
+    let c = graph.create_and_connect_weighted(b, "c", -4, 2);
+    graph.graph.edges.connect_weighted(c, a, 1);
+    let d = graph.create_and_connect_weighted(c, "d", 1, 0);
+
+    let cycle = bellman_ford(&graph.graph.edges, s, graph.graph.vertices.len()).unwrap_err();
+    assert_eq!(cycle, NegativeCycle{vertices: vec![a, b, c]});
+
+    // The cycle is not reachable from d
+    let paths = bellman_ford(&graph.graph.edges, d, graph.graph.vertices.len()).unwrap();
+    assert_eq!(paths.distances, vec![None, None, None, None, Some(0)]);
+}
+
+#[test]
+pub fn bellman_ford_self_loop_test(){
+    let mut graph: WeightedGraph<&str, EdgeStorage> = WeightedGraph::new();
+    let s = graph.graph.create("s", 1);
+    let a = graph.create_and_connect_weighted(s, "a", 1, 1);
+    graph.graph.edges.connect_weighted(a, a, -1);
+    let cycle = bellman_ford(&graph.graph.edges, s, graph.graph.vertices.len()).unwrap_err();
+    assert_eq!(cycle.vertices, vec![a]);
+}
+
+/// `size` x `size` grid connected in all four directions, cells with `x == wall_x` and `y > 0` are not entered.
+fn build_grid(size: usize, wall_x: usize) -> WeightedGraph<(i64, i64), EdgeStorage> {
+    let mut graph = WeightedGraph::new();
+    for y in 0..size {
+        for x in 0..size {
+            graph.graph.create((x as i64, y as i64), 4);
+        }
+    }
+    let is_wall = |x: usize, y: usize| x == wall_x && y > 0;
+    for y in 0..size {
+        for x in 0..size {
+            let vertex = (y * size + x) as VHandle;
+            let neighbors = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+            for (next_x, next_y) in neighbors {
+                if next_x >= size || next_y >= size || is_wall(next_x, next_y) {
+                    continue;
+                }
+                graph.graph.edges.connect_weighted(vertex, (next_y * size + next_x) as VHandle, 1);
+            }
+        }
+    }
+    return graph;
+}
+
+#[test]
+pub fn astar_test(){
+    let size = 30;
+    let graph = build_grid(size, 15);
+    let vertices_count = graph.graph.vertices.len();
+    let start = (size * 10) as VHandle;
+    let goal = (size * 10 + 20) as VHandle;
+    let (goal_x, goal_y) = graph.graph.vertices[goal];
+    let manhattan = |vertex: VHandle| {
+        let (x, y) = graph.graph.vertices[vertex];
+        return (x - goal_x).abs() + (y - goal_y).abs();
+    };
+
+    let path = astar(&graph.graph.edges, start, goal, vertices_count, manhattan).unwrap();
+    let expected = dijkstra_to(&graph.graph.edges, start, goal, vertices_count).unwrap();
+    assert_eq!(path.cost, expected.cost);
+    assert_eq!(path.cost, 40); // Up to the gap in the first row and back down
+    assert_eq!(path.vertices.len(), 41);
+    assert_eq!(*path.vertices.first().unwrap(), start);
+    assert_eq!(*path.vertices.last().unwrap(), goal);
+
+    let mut guided = 0;
+    astar_with(&graph.graph.edges, start, goal, vertices_count, manhattan, |_vertex| { guided += 1; Resume });
+    let mut blind = 0;
+    astar_with(&graph.graph.edges, start, goal, vertices_count, |_vertex| 0, |_vertex| { blind += 1; Resume });
+    assert!(guided < blind);
+}
+
+#[test]
+pub fn astar_budget_test(){
+    let graph = build_grid(10, 10);
+    let vertices_count = graph.graph.vertices.len();
+    let mut budget = 5;
+    let outcome = astar_with(&graph.graph.edges, 0, 99, vertices_count, |_vertex| 0, |_vertex| {
+        budget -= 1;
+        if budget == 0 { End } else { Resume }
+    });
+    assert_eq!(outcome, SearchOutcome::Aborted);
+
+    let outcome = astar_with(&graph.graph.edges, 0, 99, vertices_count, |_vertex| 0, |_vertex| Resume);
+    assert_eq!(outcome.path().unwrap().cost, 18);
+}
+
+#[test]
+pub fn astar_unreachable_test(){
+    let graph = build_graph();
+    let vertices_count = graph.graph.vertices.len();
+    assert_eq!(astar_with(&graph.graph.edges, 0, 5, vertices_count, |_vertex| 0, |_vertex| Resume), SearchOutcome::Unreachable);
+    assert_eq!(astar(&graph.graph.edges, 0, 4, vertices_count, |_vertex| 0), Some(Path{vertices: vec![0, 2, 1, 3, 4], cost: 8}));
+}
+
+/// Deterministic sparse graph with some negative edges but without negative cycles: edges only go from
+/// lower to higher handles, except for positive back edges heavier than any forward path can save.
+fn build_random_dag(vertices_count: usize) -> WeightedGraph<usize, EdgeStorage> {
+    let mut graph = WeightedGraph::new();
+    for i in 0..vertices_count {
+        graph.graph.create(i, 4);
+    }
+    let mut seed: u64 = 42;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        return (seed >> 33) as usize;
+    };
+    for from in 0..vertices_count - 1 {
+        for _ in 0..3 {
+            let to = from + 1 + next() % (vertices_count - from - 1);
+            let weight = (next() % 20) as i32 - 5;
+            graph.graph.edges.connect_weighted(from as VHandle, to as VHandle, weight);
+        }
+        if from % 3 == 0 {
+            graph.graph.edges.connect_weighted((from + 1) as VHandle, 0, 5 * vertices_count as i32);
+        }
+    }
+    return graph;
+}
+
+#[test]
+pub fn all_pairs_test(){
+    let graph = build_random_dag(40);
+    let vertices_count = graph.graph.vertices.len();
+    let floyd = floyd_warshall(&graph.graph.edges, vertices_count).unwrap();
+    let johnson = johnson(&graph.graph.edges, vertices_count).unwrap();
+    assert_eq!(floyd.distances, johnson.distances);
+
+    for from in 0..vertices_count as VHandle {
+        let paths = bellman_ford(&graph.graph.edges, from, vertices_count).unwrap();
+        assert_eq!(floyd.row(from), &paths.distances[..]);
+        for to in 0..vertices_count as VHandle {
+            for matrix in [&floyd, &johnson] {
+                let Some(path) = matrix.path(from, to) else {
+                    assert!(!paths.is_reachable(to));
+                    continue;
+                };
+                assert_eq!(path.vertices[0], from);
+                assert_eq!(*path.vertices.last().unwrap(), to);
+                let cost: i64 = path.vertices.windows(2).map(|step| {
+                    graph.graph.edges.edges(step[0]).iter().filter(|edge| vh(**edge) == step[1]).map(|edge| wgt(*edge) as i64).min().unwrap()
+                }).sum();
+                assert_eq!(cost, path.cost);
+            }
+        }
+    }
+}
+
+#[test]
+pub fn all_pairs_small_test(){
+    let graph = build_graph();
+    let vertices_count = graph.graph.vertices.len();
+    for matrix in [floyd_warshall(&graph.graph.edges, vertices_count).unwrap(), johnson(&graph.graph.edges, vertices_count).unwrap()] {
+        assert_eq!(matrix.distance(0, 4), Some(8));
+        assert_eq!(matrix.distance(4, 3), Some(7));
+        assert_eq!(matrix.distance(5, 5), Some(0));
+        assert_eq!(matrix.distance(0, 5), None);
+        assert_eq!(matrix.path(3, 1), Some(Path{vertices: vec![3, 4, 0, 2, 1], cost: 8}));
+        assert_eq!(matrix.path(2, 2), Some(Path{vertices: vec![2], cost: 0}));
+    }
+}
+
+#[test]
+pub fn all_pairs_negative_cycle_test(){
+    let mut graph: WeightedGraph<&str, EdgeStorage> = WeightedGraph::new();
+    let s = graph.graph.create("s", 1);
+    let a = graph.create_and_connect_weighted(s, "a", 5, 1);
+    let b = graph.create_and_connect_weighted(a, "b", 2, 1);
+    let c = graph.create_and_connect_weighted(b, "c", -4, 1);
+    graph.graph.edges.connect_weighted(c, a, 1);
+    let vertices_count = graph.graph.vertices.len();
+
+    let expected = NegativeCycle{vertices: vec![a, b, c]};
+    assert_eq!(floyd_warshall(&graph.graph.edges, vertices_count).unwrap_err(), expected);
+    assert_eq!(johnson(&graph.graph.edges, vertices_count).unwrap_err(), expected);
+}
+
+#[test]
+pub fn floyd_warshall_overflow_test(){
+    // Every cycle is hugely negative, the distances must not be pushed around long enough to overflow
+    let vertices_count = 40;
+    let mut graph: WeightedGraph<usize, EdgeStorage> = WeightedGraph::new();
+    for i in 0..vertices_count {
+        graph.graph.create(i, vertices_count);
+    }
+    for from in 0..vertices_count {
+        for to in 0..vertices_count {
+            if from != to {
+                graph.graph.edges.connect_weighted(from as VHandle, to as VHandle, i32::MIN);
+            }
+        }
+    }
+    let cycle = floyd_warshall(&graph.graph.edges, vertices_count).unwrap_err();
+    assert!(cycle.vertices.len() >= 2);
+}
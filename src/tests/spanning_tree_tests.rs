@@ -0,0 +1,110 @@
+use crate::algorithms::{kruskal, kruskal_with, prim, prim_with, weakly_connected_components};
+use crate::edge_storage::EdgeStorage;
+use crate::handles::types::VHandle;
+use crate::undirected_graph::UndirectedGraph;
+
+/// The textbook nine vertex graph with a minimum spanning tree of weight 37, plus the pair 9 - 10 and the
+/// isolated vertex 11.
+fn build_graph() -> UndirectedGraph<VHandle, EdgeStorage> {
+    let mut graph = UndirectedGraph::new();
+    for i in 0..12 {
+        graph.graph.create(i, 4);
+    }
+    let links = [(0, 1, 4), (0, 7, 8), (1, 2, 8), (1, 7, 11), (2, 3, 7), (2, 8, 2), (2, 5, 4), (3, 4, 9),
+        (3, 5, 14), (4, 5, 10), (5, 6, 2), (6, 7, 1), (6, 8, 6), (7, 8, 7), (9, 10, 3)];
+    for (a, b, weight) in links {
+        graph.connect_weighted(a, b, weight);
+    }
+    return graph;
+}
+
+#[test]
+pub fn kruskal_test(){
+    let graph = build_graph();
+    let forest = kruskal(&graph.graph.edges, graph.graph.vertices.len());
+    assert_eq!(forest.total_weight, 40);
+    assert_eq!(forest.tree_count, 3);
+    assert_eq!(forest.edges.len(), 12 - 3);
+    assert!(!forest.is_spanning_tree());
+    let summed: i64 = forest.edges.iter().map(|(_src, _target, weight)| *weight as i64).sum();
+    assert_eq!(summed, forest.total_weight);
+}
+
+#[test]
+pub fn prim_test(){
+    let graph = build_graph();
+    let vertices_count = graph.graph.vertices.len();
+    let forest = prim(&graph.graph.edges, vertices_count);
+    assert_eq!(forest.total_weight, 40);
+    assert_eq!(forest.tree_count, 3);
+    assert_eq!(forest.edges.len(), 12 - 3);
+
+    // The forest edges connect exactly what the graph connects
+    let mut tree: UndirectedGraph<VHandle, EdgeStorage> = UndirectedGraph::new();
+    for i in 0..vertices_count as VHandle {
+        tree.graph.create(i, 1);
+    }
+    for (src, target, weight) in &forest.edges {
+        tree.connect_weighted(*src, *target, *weight);
+    }
+    assert_eq!(weakly_connected_components(&tree.graph.edges, vertices_count), weakly_connected_components(&graph.graph.edges, vertices_count));
+}
+
+#[test]
+pub fn spanning_tree_single_component_test(){
+    let mut graph = build_graph();
+    graph.connect_weighted(8, 9, 20);
+    graph.connect_weighted(11, 4, 1);
+    let vertices_count = graph.graph.vertices.len();
+    for forest in [kruskal(&graph.graph.edges, vertices_count), prim(&graph.graph.edges, vertices_count)] {
+        assert!(forest.is_spanning_tree());
+        assert_eq!(forest.total_weight, 37 + 3 + 20 + 1);
+    }
+}
+
+#[test]
+pub fn spanning_tree_graph_test(){
+    let graph = build_graph();
+    let forest = prim(&graph.graph.edges, graph.graph.vertices.len());
+    let mut tree_graph = forest.to_tree_graph(6, |vertex| vertex * 10);
+    let tree_view = tree_graph.tree_view();
+
+    assert_eq!(tree_view.values[4 as VHandle], 40);
+    assert_eq!(tree_view.get_root(6), 6);
+    assert_eq!(tree_view.get_root(0), 6);
+    assert_eq!(tree_view.get_parent(7), 6);
+    assert_eq!(tree_view.get_parent(1), 2); // Prim breaks the tie between 0 - 7 and 1 - 2 towards the smaller vertex
+    assert_eq!(tree_view.get_parent(0), 1);
+    assert_eq!(tree_view.get_root(10), 9);
+    assert_eq!(tree_view.get_parent(10), 9);
+    assert_eq!(tree_view.get_root(11), 11);
+    assert_eq!(tree_view.get_children(11).len(), 0);
+
+    // Every vertex but the roots 6, 9 and 11 hangs below exactly one parent
+    let children: usize = (0..12).map(|vertex| tree_view.get_children(vertex).len()).sum();
+    assert_eq!(children, 12 - 3);
+}
+
+#[test]
+pub fn spanning_tree_removed_vertex_test(){
+    let mut graph = build_graph();
+    for vertex in [9, 10, 11] {
+        graph.graph.remove(vertex);
+    }
+    let vertices_count = graph.graph.vertices.len();
+    assert!(!kruskal(&graph.graph.edges, vertices_count).is_spanning_tree());
+
+    let is_alive = |vertex: VHandle| graph.graph.is_alive(vertex);
+    for forest in [kruskal_with(&graph.graph.edges, vertices_count, is_alive), prim_with(&graph.graph.edges, vertices_count, is_alive)] {
+        assert!(forest.is_spanning_tree());
+        assert_eq!(forest.total_weight, 37);
+        assert_eq!(forest.edges.len(), 8);
+
+        // Values are only asked for covered vertices, the removed slots stay removed in the tree graph
+        let tree_graph = forest.to_tree_graph(0, |vertex| graph.graph.vertices[vertex]);
+        assert_eq!(tree_graph.vertices.len(), vertices_count);
+        assert_eq!(tree_graph.vertices.count(), 9);
+        assert!(!tree_graph.is_alive(10));
+        assert_eq!(tree_graph.vertices[4 as VHandle], graph.graph.vertices[4 as VHandle]);
+    }
+}
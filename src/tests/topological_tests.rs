@@ -0,0 +1,107 @@
+use crate::algorithms::{find_cycle, find_cycle_with, topological_sort, topological_sort_by_key, topological_sort_by_key_with, topological_sort_dfs, topological_sort_dfs_with, topological_sort_with, Cycle};
+use crate::edge_storage::EdgeStorage;
+use crate::graph::Graph;
+use crate::handles::types::VHandle;
+use crate::traits::{EdgeOperator, EdgeStore};
+
+/// Build steps, an edge points from a step to the steps depending on it.
+fn build_dependencies() -> Graph<&'static str, EdgeStorage> {
+    let mut graph = Graph::new();
+    let fetch = graph.create("fetch", 2);
+    let configure = graph.create("configure", 2);
+    let compile = graph.create("compile", 2);
+    let link = graph.create("link", 1);
+    let docs = graph.create("docs", 1);
+    let package = graph.create("package", 0);
+    graph.edges.connect(fetch, configure);
+    graph.edges.connect(fetch, docs);
+    graph.edges.connect(configure, compile);
+    graph.edges.connect(compile, link);
+    graph.edges.connect(link, package);
+    graph.edges.connect(docs, package);
+    graph.edges.connect(configure, link);
+    return graph;
+}
+
+fn assert_topological(graph: &Graph<&'static str, EdgeStorage>, order: &[VHandle]) {
+    assert_eq!(order.len(), graph.vertices.len());
+    let mut position = vec![usize::MAX; order.len()];
+    for (index, vertex) in order.iter().enumerate() {
+        position[*vertex as usize] = index;
+    }
+    for vertex in 0..order.len() as VHandle {
+        for edge in graph.edges.edges(vertex) {
+            assert!(position[vertex as usize] < position[crate::handles::vh(*edge) as usize]);
+        }
+    }
+}
+
+#[test]
+pub fn topological_sort_test(){
+    let graph = build_dependencies();
+    let vertices_count = graph.vertices.len();
+    let kahn = topological_sort(&graph.edges, vertices_count).unwrap();
+    let depth_first = topological_sort_dfs(&graph.edges, vertices_count).unwrap();
+    assert_topological(&graph, &kahn);
+    assert_topological(&graph, &depth_first);
+    assert_eq!(find_cycle(&graph.edges, vertices_count), None);
+}
+
+#[test]
+pub fn topological_sort_by_key_test(){
+    let graph = build_dependencies();
+    let vertices_count = graph.vertices.len();
+    let by_handle = topological_sort_by_key(&graph.edges, vertices_count, |vertex| vertex).unwrap();
+    assert_eq!(by_handle, vec![0, 1, 2, 3, 4, 5]);
+
+    // Alphabetical among the ready steps, docs goes before everything but fetch
+    let by_name = topological_sort_by_key(&graph.edges, vertices_count, |vertex| graph.vertices[vertex]).unwrap();
+    let names: Vec<&str> = by_name.iter().map(|vertex| graph.vertices[*vertex]).collect();
+    assert_eq!(names, vec!["fetch", "configure", "compile", "docs", "link", "package"]);
+}
+
+#[test]
+pub fn topological_cycle_test(){
+    let mut graph = build_dependencies();
+    let vertices_count = graph.vertices.len();
+    graph.edges.connect(3, 1); // link -> configure closes configure -> compile -> link and configure -> link
+    let expected = [Cycle{vertices: vec![1, 2, 3]}, Cycle{vertices: vec![1, 3]}];
+
+    let error = topological_sort(&graph.edges, vertices_count).unwrap_err();
+    assert!(expected.contains(&error));
+    let error = topological_sort_by_key(&graph.edges, vertices_count, |vertex| vertex).unwrap_err();
+    assert!(expected.contains(&error));
+    let error = topological_sort_dfs(&graph.edges, vertices_count).unwrap_err();
+    assert_eq!(error, Cycle{vertices: vec![1, 2, 3]});
+    assert_eq!(error.to_string(), "cycle 1 -> 2 -> 3 -> 1");
+
+    let mut graph = build_dependencies();
+    graph.edges.connect(5, 5);
+    assert_eq!(topological_sort_dfs(&graph.edges, vertices_count).unwrap_err(), Cycle{vertices: vec![5]});
+}
+
+#[test]
+pub fn topological_removed_vertex_test(){
+    let mut graph = build_dependencies();
+    let vertices_count = graph.vertices.len();
+    let docs = 4;
+    graph.remove(docs);
+
+    // The plain functions order the dead slot like a vertex
+    assert!(topological_sort(&graph.edges, vertices_count).unwrap().contains(&docs));
+    let is_alive = |vertex| graph.is_alive(vertex);
+    let orders = [
+        topological_sort_with(&graph.edges, vertices_count, is_alive).unwrap(),
+        topological_sort_by_key_with(&graph.edges, vertices_count, |vertex| vertex, is_alive).unwrap(),
+        topological_sort_dfs_with(&graph.edges, vertices_count, is_alive).unwrap(),
+    ];
+    for order in orders {
+        let names: Vec<&str> = order.iter().map(|vertex| graph.vertices[*vertex]).collect();
+        assert_eq!(names, vec!["fetch", "configure", "compile", "link", "package"]);
+    }
+
+    graph.edges.connect(3, 1);
+    let is_alive = |vertex| graph.is_alive(vertex);
+    assert_eq!(find_cycle_with(&graph.edges, vertices_count, is_alive), Some(Cycle{vertices: vec![1, 2, 3]}));
+    assert!(topological_sort_with(&graph.edges, vertices_count, is_alive).is_err());
+}
@@ -0,0 +1,126 @@
+use crate::algorithms::{bfs, dfs, Bfs, Dfs, DfsEvent, Visit};
+use crate::algorithms::ControlFlow::Resume;
+use crate::tests::{build_cyclic_tree_graph, TreeGraph};
+use crate::handles::types::VHandle;
+
+#[test]
+pub fn bfs_iter_test(){
+    let TreeGraph{mut graph, root} = build_cyclic_tree_graph();
+    let visits: Vec<Visit<VHandle>> = Bfs::new(&graph.edges, root, graph.vertices.len()).collect();
+    let names: Vec<&str> = visits.iter().map(|visit| graph.vertices[visit.vertex]).collect();
+    assert_eq!(names, vec!["root", "a", "b", "c", "a_a", "a_b", "b_a", "b_a_a"]);
+    assert_eq!(visits[0].parent, None);
+    assert_eq!(visits[6].depth, 2);
+    assert_eq!(graph.vertices[visits[6].parent.unwrap()], "b");
+    assert_eq!(visits[7].depth, 3);
+
+    let mut callback_order = Vec::new();
+    let vertices_count = graph.vertices.len();
+    bfs(&mut graph.edges, root, vertices_count, |_edges, handle|{
+        callback_order.push(handle);
+        Resume
+    });
+    let iter_order: Vec<VHandle> = visits.iter().map(|visit| visit.vertex).collect();
+    assert_eq!(iter_order, callback_order);
+}
+
+#[test]
+pub fn bfs_iter_skip_children_test(){
+    let TreeGraph{graph, root} = build_cyclic_tree_graph();
+    let mut traversal = Bfs::new(&graph.edges, root, graph.vertices.len());
+    let mut names = Vec::new();
+    while let Some(visit) = traversal.next() {
+        names.push(graph.vertices[visit.vertex]);
+        if graph.vertices[visit.vertex] == "a" {
+            traversal.skip_children();
+        }
+    }
+    assert_eq!(names, vec!["root", "a", "b", "c", "b_a", "b_a_a"]);
+}
+
+#[test]
+pub fn dfs_iter_test(){
+    let TreeGraph{mut graph, root} = build_cyclic_tree_graph();
+    let events: Vec<DfsEvent<VHandle>> = Dfs::new(&graph.edges, root, graph.vertices.len()).collect();
+    assert_eq!(events.len(), 16);
+    assert_eq!(events[0], DfsEvent::Pre(Visit{vertex: root, depth: 0, parent: None}));
+    assert_eq!(events[15], DfsEvent::Post(Visit{vertex: root, depth: 0, parent: None}));
+
+    let pre: Vec<&str> = Dfs::new(&graph.edges, root, graph.vertices.len()).pre_order().map(|visit| graph.vertices[visit.vertex]).collect();
+    let post: Vec<&str> = Dfs::new(&graph.edges, root, graph.vertices.len()).post_order().map(|visit| graph.vertices[visit.vertex]).collect();
+    assert_eq!(pre, vec!["root", "a", "a_a", "a_b", "b", "b_a", "b_a_a", "c"]);
+    assert_eq!(post, vec!["a_a", "a_b", "a", "b_a_a", "b_a", "b", "c", "root"]);
+
+    let mut callback_pre = Vec::new();
+    let mut callback_post = Vec::new();
+    let vertices_count = graph.vertices.len();
+    dfs(&mut graph.edges, root, vertices_count, |_edges, handle|{
+        callback_pre.push(handle);
+        Resume
+    }, |_edges, handle|{
+        callback_post.push(handle);
+    });
+    let pre: Vec<VHandle> = Dfs::new(&graph.edges, root, vertices_count).pre_order().map(|visit| visit.vertex).collect();
+    let post: Vec<VHandle> = Dfs::new(&graph.edges, root, vertices_count).post_order().map(|visit| visit.vertex).collect();
+    assert_eq!(pre, callback_pre);
+    assert_eq!(post, callback_post);
+}
+
+#[test]
+pub fn dfs_iter_skip_children_test(){
+    let TreeGraph{graph, root} = build_cyclic_tree_graph();
+    let mut traversal = Dfs::new(&graph.edges, root, graph.vertices.len());
+    let mut names = Vec::new();
+    while let Some(event) = traversal.next() {
+        if let DfsEvent::Pre(visit) = event {
+            names.push(graph.vertices[visit.vertex]);
+            if graph.vertices[visit.vertex] == "b" {
+                traversal.skip_children();
+            }
+        }
+    }
+    assert_eq!(names, vec!["root", "a", "a_a", "a_b", "b", "c"]);
+}
+
+#[test]
+pub fn dfs_iter_skip_after_post_test(){
+    let TreeGraph{graph, root} = build_cyclic_tree_graph();
+    let mut traversal = Dfs::new(&graph.edges, root, graph.vertices.len());
+    let mut names = Vec::new();
+    while let Some(event) = traversal.next() {
+        match event {
+            DfsEvent::Pre(visit) => names.push(graph.vertices[visit.vertex]),
+            // Once a_a is finished the call must not prune a_b, the next child of its parent
+            DfsEvent::Post(visit) => if graph.vertices[visit.vertex] == "a_a" {
+                traversal.skip_children();
+            }
+        }
+    }
+    assert_eq!(names, vec!["root", "a", "a_a", "a_b", "b", "b_a", "b_a_a", "c"]);
+}
+
+#[test]
+pub fn traversal_compose_test(){
+    let TreeGraph{graph, root} = build_cyclic_tree_graph();
+    let vertices_count = graph.vertices.len();
+
+    let shallow: Vec<VHandle> = Bfs::new(&graph.edges, root, vertices_count)
+        .take_while(|visit| visit.depth < 2)
+        .map(|visit| visit.vertex)
+        .collect();
+    assert_eq!(shallow.len(), 4);
+
+    // Two traversals over the same storage advanced in lockstep
+    let pairs: Vec<(VHandle, VHandle)> = Bfs::new(&graph.edges, root, vertices_count)
+        .zip(Dfs::new(&graph.edges, root, vertices_count).pre_order())
+        .map(|(bfs_visit, dfs_visit)| (bfs_visit.vertex, dfs_visit.vertex))
+        .collect();
+    assert_eq!(pairs.len(), 8);
+    assert_eq!(pairs[0], (root, root));
+
+    // Pausing and resuming
+    let mut traversal = Bfs::new(&graph.edges, root, vertices_count);
+    let first: Vec<VHandle> = traversal.by_ref().take(3).map(|visit| visit.vertex).collect();
+    let rest: Vec<VHandle> = traversal.map(|visit| visit.vertex).collect();
+    assert_eq!(first.len() + rest.len(), 8);
+}
@@ -0,0 +1,131 @@
+use crate::edge_storage::{EdgeStorage, GrowthPolicy};
+use crate::graph::Error;
+use crate::handles::types::VHandle;
+use crate::handles::{vh, wgt};
+use crate::traits::{EdgeStore, IncomingEdgeStore};
+use crate::undirected_graph::UndirectedGraph;
+
+#[test]
+pub fn undirected_connect_test(){
+    let mut graph: UndirectedGraph<i32, EdgeStorage> = UndirectedGraph::new();
+    let a = graph.graph.create(0, 2);
+    let b = graph.graph.create(1, 2);
+    let c = graph.graph.create(2, 2);
+
+    graph.connect_weighted(a, b, 5);
+    graph.connect(b, c);
+
+    assert!(graph.is_connected(a, b));
+    assert!(graph.is_connected(b, a));
+    assert!(graph.is_connected(c, b));
+    assert!(!graph.is_connected(a, c));
+    assert_eq!(graph.weight(b, a), Some(5));
+    assert_eq!(graph.degree(b), Ok(2));
+    assert_eq!(graph.edge_count(), 2);
+
+    let mut neighbors: Vec<VHandle> = graph.neighbors(b).unwrap().collect();
+    neighbors.sort();
+    assert_eq!(neighbors, vec![a, c]);
+
+    graph.set_weight(a, b, 9).unwrap();
+    assert_eq!(graph.weight(a, b), Some(9));
+    assert_eq!(graph.weight(b, a), Some(9));
+    assert_eq!(graph.set_weight(a, c, 1), Err(Error::EdgeNotFound { src: a, target: c }));
+
+    graph.disconnect(b, a);
+    assert!(!graph.is_connected(a, b));
+    assert!(!graph.is_connected(b, a));
+    assert_eq!(graph.degree(a), Ok(0));
+    assert_eq!(graph.edge_count(), 1);
+}
+
+#[test]
+pub fn undirected_self_loop_test(){
+    let mut graph: UndirectedGraph<i32, EdgeStorage> = UndirectedGraph::new();
+    let a = graph.graph.create(0, 1);
+    let b = graph.create_and_connect_leaf(a, 1);
+
+    graph.connect(a, a);
+    assert_eq!(graph.graph.edges.len(a), 2);
+    assert_eq!(graph.degree(a), Ok(3));
+    assert_eq!(graph.degree(b), Ok(1));
+    assert_eq!(graph.edge_count(), 2);
+
+    graph.disconnect(a, a);
+    assert_eq!(graph.degree(a), Ok(1));
+}
+
+#[test]
+pub fn undirected_rollback_test(){
+    let mut graph: UndirectedGraph<i32, EdgeStorage> = UndirectedGraph::new();
+    graph.graph.edges.set_growth_policy(GrowthPolicy::Fixed);
+    let a = graph.graph.create(0, 1);
+    let b = graph.graph.create(1, 0);
+
+    assert_eq!(graph.try_connect(a, b), Err(Error::CapacityExceeded { vertex: b, capacity: 0 }));
+    assert!(!graph.is_connected(a, b));
+    assert_eq!(graph.try_connect(a, 7), Err(Error::InvalidHandle(7)));
+    assert_eq!(graph.try_disconnect(a, b), Err(Error::EdgeNotFound { src: a, target: b }));
+}
+
+#[test]
+pub fn undirected_parallel_edge_test(){
+    let mut graph: UndirectedGraph<i32, EdgeStorage> = UndirectedGraph::new();
+    graph.graph.edges.set_growth_policy(GrowthPolicy::Fixed);
+    let a = graph.graph.create(0, 2);
+    let b = graph.graph.create(1, 2);
+    let c = graph.graph.create(2, 0);
+
+    graph.connect_weighted(a, b, 5);
+    assert_eq!(graph.try_connect_weighted(b, a, 9), Err(Error::EdgeExists { src: b, target: a }));
+    graph.connect(a, a);
+    assert_eq!(graph.try_connect(a, a), Err(Error::EdgeExists { src: a, target: a }));
+    assert_eq!(graph.graph.edges.len(a), 2);
+    assert_eq!(graph.graph.edges.len(b), 1);
+
+    // The rollback only takes back the edge it added
+    assert_eq!(graph.try_connect(b, c), Err(Error::CapacityExceeded { vertex: c, capacity: 0 }));
+    assert_eq!(graph.neighbors(b).unwrap().collect::<Vec<VHandle>>(), vec![a]);
+    assert_eq!(graph.weight(a, b), Some(5));
+    assert_eq!(graph.weight(b, a), Some(5));
+
+    graph.set_weight(b, a, 7).unwrap();
+    graph.disconnect(a, b);
+    assert_eq!(graph.graph.edges.edges(a).iter().map(|edge| vh(*edge)).collect::<Vec<VHandle>>(), vec![a]);
+    assert_eq!(graph.graph.edges.len(b), 0);
+}
+
+#[test]
+pub fn undirected_invalid_handle_test(){
+    let mut graph: UndirectedGraph<i32, EdgeStorage> = UndirectedGraph::new();
+    let a = graph.graph.create(0, 1);
+    let b = graph.create_and_connect_leaf(a, 1);
+    graph.graph.remove(b);
+
+    assert_eq!(graph.weight(a, b), None);
+    assert!(!graph.is_connected(a, 9));
+    assert_eq!(graph.degree(b).err(), Some(Error::InvalidHandle(b)));
+    assert_eq!(graph.neighbors(9).err(), Some(Error::InvalidHandle(9)));
+    assert_eq!(graph.degree(a), Ok(0));
+    assert_eq!(graph.edge_count(), 0);
+}
+
+#[test]
+pub fn undirected_set_weight_incoming_test(){
+    let mut graph: UndirectedGraph<i32, EdgeStorage> = UndirectedGraph::new();
+    graph.graph.edges.track_incoming();
+    let a = graph.graph.create(0, 2);
+    let b = graph.graph.create(1, 2);
+    let c = graph.graph.create(2, 2);
+    graph.connect_weighted(a, b, 3);
+
+    assert_eq!(graph.set_weight(a, b, 7), Ok(()));
+    assert_eq!(graph.weight(a, b), Some(7));
+    assert_eq!(graph.weight(b, a), Some(7));
+    let incoming: Vec<(VHandle, i32)> = graph.graph.edges.in_edges(b).iter().map(|edge| (vh(*edge), wgt(*edge))).collect();
+    assert_eq!(incoming, vec![(a, 7)]);
+    let incoming: Vec<(VHandle, i32)> = graph.graph.edges.in_edges(a).iter().map(|edge| (vh(*edge), wgt(*edge))).collect();
+    assert_eq!(incoming, vec![(b, 7)]);
+
+    assert_eq!(graph.set_weight(a, c, 1), Err(Error::EdgeNotFound { src: a, target: c }));
+}
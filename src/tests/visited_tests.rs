@@ -0,0 +1,131 @@
+use std::thread;
+use crate::algorithms::{bfs, bfs_with_visited, dfs, dfs_with_visited, Bfs, Dfs, VisitedBits, VisitedEpochs};
+use crate::algorithms::ControlFlow::{Continue, End, Resume};
+use crate::tests::{build_cyclic_tree_graph, TreeGraph};
+use crate::handles::types::VHandle;
+use crate::traits::VisitedSet;
+
+#[test]
+pub fn visited_sets_test(){
+    let mut bits = VisitedBits::new(0);
+    let mut epochs = VisitedEpochs::default();
+    VisitedSet::<VHandle>::reset(&mut bits, 130);
+    VisitedSet::<VHandle>::reset(&mut epochs, 130);
+    for vertex in [0 as VHandle, 63, 64, 129] {
+        assert!(!bits.is_visited(vertex));
+        assert!(bits.visit(vertex));
+        assert!(!bits.visit(vertex));
+        assert!(!epochs.is_visited(vertex));
+        assert!(epochs.visit(vertex));
+        assert!(!epochs.visit(vertex));
+    }
+    assert!(!bits.is_visited(1 as VHandle));
+    VisitedSet::<VHandle>::reset(&mut bits, 130);
+    VisitedSet::<VHandle>::reset(&mut epochs, 130);
+    assert!(!bits.is_visited(64 as VHandle));
+    assert!(!epochs.is_visited(64 as VHandle));
+}
+
+#[test]
+pub fn visited_epochs_wraparound_test(){
+    let mut epochs = VisitedEpochs::new(4);
+    epochs.epoch = u32::MAX - 1;
+    VisitedSet::<VHandle>::reset(&mut epochs, 4);
+    assert!(epochs.visit(2 as VHandle));
+    assert_eq!(epochs.epoch(), u32::MAX);
+    VisitedSet::<VHandle>::reset(&mut epochs, 4);
+    assert_eq!(epochs.epoch(), 1);
+    for vertex in 0..4 as VHandle {
+        assert!(!epochs.is_visited(vertex));
+    }
+}
+
+#[test]
+pub fn bfs_with_visited_test(){
+    let TreeGraph{mut graph, root} = build_cyclic_tree_graph();
+    let vertices_count = graph.vertices.len();
+    let mut expected = Vec::new();
+    bfs(&mut graph.edges, root, vertices_count, |_edges, handle|{
+        expected.push(handle);
+        Resume
+    });
+
+    let mut epochs = VisitedEpochs::default();
+    for _ in 0..3 {
+        let mut order = Vec::new();
+        bfs_with_visited(&graph.edges, root, vertices_count, &mut epochs, |_edges, handle|{
+            order.push(handle);
+            Resume
+        });
+        assert_eq!(order, expected);
+    }
+
+    let mut order = Vec::new();
+    bfs_with_visited(&graph.edges, root, vertices_count, &mut VisitedBits::default(), |_edges, handle|{
+        order.push(handle);
+        if graph.vertices[handle] == "b" { Continue } else if graph.vertices[handle] == "a_a" { End } else { Resume }
+    });
+    let names: Vec<&str> = order.iter().map(|handle| graph.vertices[*handle]).collect();
+    assert_eq!(names, vec!["root", "a", "b", "c", "a_a"]);
+
+    let iter_order: Vec<VHandle> = Bfs::with_visited(&graph.edges, root, vertices_count, &mut epochs).map(|visit| visit.vertex).collect();
+    assert_eq!(iter_order, expected);
+}
+
+#[test]
+pub fn dfs_with_visited_test(){
+    let TreeGraph{mut graph, root} = build_cyclic_tree_graph();
+    let vertices_count = graph.vertices.len();
+    let mut expected_pre = Vec::new();
+    let mut expected_post = Vec::new();
+    dfs(&mut graph.edges, root, vertices_count, |_edges, handle|{
+        expected_pre.push(handle);
+        Resume
+    }, |_edges, handle|{
+        expected_post.push(handle);
+    });
+
+    let mut epochs = VisitedEpochs::default();
+    for _ in 0..3 {
+        let mut pre = Vec::new();
+        let mut post = Vec::new();
+        dfs_with_visited(&graph.edges, root, vertices_count, &mut epochs, |_edges, handle|{
+            pre.push(handle);
+            Resume
+        }, |_edges, handle|{
+            post.push(handle);
+        });
+        assert_eq!(pre, expected_pre);
+        assert_eq!(post, expected_post);
+    }
+
+    let pre: Vec<VHandle> = Dfs::with_visited(&graph.edges, root, vertices_count, &mut epochs).pre_order().map(|visit| visit.vertex).collect();
+    assert_eq!(pre, expected_pre);
+}
+
+#[test]
+pub fn concurrent_traversal_test(){
+    let TreeGraph{graph, root} = build_cyclic_tree_graph();
+    let vertices_count = graph.vertices.len();
+    let edges = &graph.edges;
+    let expected: Vec<VHandle> = Bfs::new(edges, root, vertices_count).map(|visit| visit.vertex).collect();
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..4).map(|_| scope.spawn(|| {
+            let mut epochs = VisitedEpochs::new(vertices_count);
+            let mut last = Vec::new();
+            for _ in 0..1000 {
+                last.clear();
+                bfs_with_visited(edges, root, vertices_count, &mut epochs, |_edges, handle|{
+                    last.push(handle);
+                    Resume
+                });
+            }
+            return last;
+        })).collect();
+
+        for worker in workers {
+            assert_eq!(worker.join().unwrap(), expected);
+        }
+    });
+}
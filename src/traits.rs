@@ -1,44 +1,123 @@
-use crate::handles::Slot;
-use crate::handles::types::{PackedEdge, VHandle, Weight};
+use crate::graph::Error;
+use crate::handles::EdgePacking;
 
 pub trait Transformer<T>{
     fn transform(&mut self, transform_fn: fn(&mut [T]));
     fn async_transform(&mut self, transform_fn: fn(&mut [T]));
 }
 
-pub trait EdgeOperator {
-    fn add_edges(&mut self, src: VHandle, targets: &[PackedEdge]);
-    fn extend_edge_storage(&mut self, size: Slot) -> Slot;
-    fn disconnect(&mut self, src_handle: VHandle, handle: VHandle);
-    fn connect(&mut self, from: VHandle, to: VHandle);
+/// Selects the handle width a storage works with.
+pub trait Packed {
+    type Edge: EdgePacking;
 }
 
-pub trait WeightedEdgeOperator {
-    fn connect_weighted(&mut self, from: VHandle, to: VHandle, weight: Weight);
+pub type EdgeOf<E> = <E as Packed>::Edge;
+pub type VHandleOf<E> = <EdgeOf<E> as EdgePacking>::VHandle;
+pub type WeightOf<E> = <EdgeOf<E> as EdgePacking>::Weight;
+
+pub trait EdgeOperator: Packed {
+    fn add_edges(&mut self, src: VHandleOf<Self>, targets: &[EdgeOf<Self>]);
+    fn try_add_edges(&mut self, src: VHandleOf<Self>, targets: &[EdgeOf<Self>]) -> Result<(), Error<VHandleOf<Self>>>;
+    fn extend_edge_storage(&mut self, size: usize) -> VHandleOf<Self>;
+    /// Frees the edge block of a removed vertex. The vertex keeps an empty block until it is reused.
+    fn release_edge_storage(&mut self, vertex: VHandleOf<Self>);
+    /// Gives a previously released vertex an empty edge block able to hold `size` edges.
+    fn reuse_edge_storage(&mut self, vertex: VHandleOf<Self>, size: usize);
+    fn disconnect(&mut self, src_handle: VHandleOf<Self>, handle: VHandleOf<Self>);
+    fn try_disconnect(&mut self, src_handle: VHandleOf<Self>, handle: VHandleOf<Self>) -> Result<(), Error<VHandleOf<Self>>>;
+    /// Like `disconnect`, but removes the most recently added of several parallel edges.
+    fn disconnect_last(&mut self, src_handle: VHandleOf<Self>, handle: VHandleOf<Self>);
+    /// Removes every edge from `src_handle` to `handle` in a single pass over the block of `src_handle`.
+    fn disconnect_all(&mut self, src_handle: VHandleOf<Self>, handle: VHandleOf<Self>);
+    /// Removes every edge pointing to `handle`. Walks the reverse adjacency when it is tracked, otherwise scans
+    /// the blocks of the vertices accepted by `is_source`.
+    fn disconnect_incoming<SourceFunc: Fn(VHandleOf<Self>) -> bool>(&mut self, handle: VHandleOf<Self>, is_source: SourceFunc);
+    fn connect(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>);
+    fn try_connect(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>) -> Result<(), Error<VHandleOf<Self>>>;
+}
+
+pub trait WeightedEdgeOperator: Packed {
+    fn connect_weighted(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>, weight: WeightOf<Self>);
+    fn try_connect_weighted(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>, weight: WeightOf<Self>) -> Result<(), Error<VHandleOf<Self>>>;
+    /// Changes the weight of the first edge from `from` to `to`.
+    fn try_set_weight(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>, weight: WeightOf<Self>) -> Result<(), Error<VHandleOf<Self>>>;
+}
+
+/// Visited marking stored inside the storage. Every traversal gets its own epoch and a vertex is visited when
+/// its flag equals the current epoch, so no cleanup is needed after a traversal, even an aborted one.
+pub trait TraverseMarker: Packed {
+    fn global_visited_flag(&self) -> usize;
+    /// Advances the epoch so that no vertex counts as visited. When the epoch would no longer fit into a flag
+    /// all flags are cleared and counting starts over.
+    fn begin_traversal(&mut self);
+    fn visited_flag(&self, vertex: VHandleOf<Self>) -> usize;
+    fn set_visited_flag(&mut self, vertex: VHandleOf<Self>, val: usize);
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn is_visited(&self, vertex: VHandleOf<Self>) -> bool {
+        return self.visited_flag(vertex) == self.global_visited_flag();
+    }
+
+    #[cfg_attr(not(debug_assertions), inline(always))]
+    fn mark_visited(&mut self, vertex: VHandleOf<Self>) {
+        let flag = self.global_visited_flag();
+        self.set_visited_flag(vertex, flag);
+    }
+}
+/// Visited state owned by the caller instead of the storage, so traversals only need `&` access to the edges
+/// and any number of them can run over the same storage at once.
+pub trait VisitedSet<H> {
+    /// Forgets every visited vertex and makes room for `vertices_count` vertices.
+    fn reset(&mut self, vertices_count: usize);
+    fn is_visited(&self, vertex: H) -> bool;
+    /// Marks the vertex as visited. Returns `false` if it already was.
+    fn visit(&mut self, vertex: H) -> bool;
+}
+
+impl<H, T: VisitedSet<H> + ?Sized> VisitedSet<H> for &mut T {
+    fn reset(&mut self, vertices_count: usize) {
+        (**self).reset(vertices_count);
+    }
+    fn is_visited(&self, vertex: H) -> bool {
+        return (**self).is_visited(vertex);
+    }
+    fn visit(&mut self, vertex: H) -> bool {
+        return (**self).visit(vertex);
+    }
 }
 
-pub trait TraverseMarker {
-    fn global_visited_flag(&self) -> Slot;
-    fn inc_global_visited_flag(&mut self);
-    fn reset_global_visited_flag(&mut self);
-    fn visited_flag(&self, vertex: VHandle) -> Slot;
-    fn inc_visited_flag(&mut self, vertex: VHandle);
-    fn set_visited_flag(&mut self, vertex: VHandle, val: Slot);
+pub trait EdgeStore: Packed {
+    fn edges_offset(&self, vertex: VHandleOf<Self>, offset: usize) -> &[EdgeOf<Self>];
+    fn edges_ptr_offset(&self, vertex: VHandleOf<Self>, offset: usize) -> *const EdgeOf<Self>;
+    fn edges(&self, vertex: VHandleOf<Self>) -> &[EdgeOf<Self>];
+    fn edges_ptr(&self, vertex: VHandleOf<Self>) -> *const EdgeOf<Self>;
+    fn len(&self, handle: VHandleOf<Self>) -> usize;
+    fn edge_block_capacity(&self, handle: VHandleOf<Self>) -> usize;
+    fn get(&self, vertex: VHandleOf<Self>, offset: usize) -> EdgeOf<Self>;
 }
-pub trait EdgeStore {
-    fn edges_offset(&self, vertex: VHandle, offset: Slot) -> &[PackedEdge];
-    fn edges_ptr_offset(&self, vertex: VHandle, offset: Slot) -> *const PackedEdge;
-    fn edges(&self, vertex: VHandle) -> &[PackedEdge];
-    fn edges_ptr(&self, vertex: VHandle) -> *const PackedEdge;
-    fn len(&self, handle: VHandle) -> Slot;
-    fn edge_block_capacity(&self, handle: VHandle) -> Slot;
-    fn get(&self, vertex: VHandle, offset: Slot) -> PackedEdge;
+
+/// Edges carrying an arbitrary payload next to the packed edge. `payloads(v)` is aligned with `edges(v)`.
+/// Edges added without a payload get `Payload::default()`.
+pub trait PayloadStore: EdgeStore {
+    type Payload;
+    fn payloads(&self, vertex: VHandleOf<Self>) -> &[Self::Payload];
+    fn payloads_mut(&mut self, vertex: VHandleOf<Self>) -> &mut [Self::Payload];
+    fn connect_with(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>, payload: Self::Payload);
+    fn try_connect_with(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>, payload: Self::Payload) -> Result<(), Error<VHandleOf<Self>>>;
+    fn connect_weighted_with(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>, weight: WeightOf<Self>, payload: Self::Payload);
+    fn try_connect_weighted_with(&mut self, from: VHandleOf<Self>, to: VHandleOf<Self>, weight: WeightOf<Self>, payload: Self::Payload) -> Result<(), Error<VHandleOf<Self>>>;
+}
+
+/// Reverse adjacency. The returned edges are packed with the source vertex and the weight of the original edge.
+pub trait IncomingEdgeStore: Packed {
+    fn in_edges(&self, vertex: VHandleOf<Self>) -> &[EdgeOf<Self>];
+    fn in_degree(&self, vertex: VHandleOf<Self>) -> usize;
 }
 
 pub trait EdgeStoreMut: EdgeStore {
-    fn edges_mut_offset(&mut self, vertex: VHandle, offset: Slot) -> &mut [PackedEdge];
-    fn edges_mut_ptr_offset(&mut self, vertex: VHandle, offset: Slot) -> *mut PackedEdge;
-    fn edges_mut_ptr(&mut self, vertex: VHandle) -> *mut PackedEdge;
-    fn edges_mut(&mut self, vertex: VHandle) -> &mut [PackedEdge];
-    fn set(&mut self, src: VHandle, val: PackedEdge, offset: Slot);
-}
\ No newline at end of file
+    fn edges_mut_offset(&mut self, vertex: VHandleOf<Self>, offset: usize) -> &mut [EdgeOf<Self>];
+    fn edges_mut_ptr_offset(&mut self, vertex: VHandleOf<Self>, offset: usize) -> *mut EdgeOf<Self>;
+    fn edges_mut_ptr(&mut self, vertex: VHandleOf<Self>) -> *mut EdgeOf<Self>;
+    fn edges_mut(&mut self, vertex: VHandleOf<Self>) -> &mut [EdgeOf<Self>];
+    fn set(&mut self, src: VHandleOf<Self>, val: EdgeOf<Self>, offset: usize);
+}
@@ -0,0 +1,155 @@
+use crate::edge_storage::EdgeStorage;
+use crate::graph::{Error, Graph};
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeOperator, EdgeStoreMut, TraverseMarker, VHandleOf, WeightedEdgeOperator, WeightOf};
+
+/// Wraps a `Graph` and keeps every edge stored in both directions, so the adjacency of a vertex is
+/// its neighbourhood. A self-loop is stored once and counts twice towards the degree.
+/// Parallel edges are rejected, so the two directions of an edge always pair up.
+pub struct UndirectedGraph<VertexType, EdgeStorageType> {
+    pub graph: Graph<VertexType, EdgeStorageType>,
+}
+
+impl <VertexType> UndirectedGraph<VertexType, EdgeStorage>{
+    pub fn new() -> Self {
+        return UndirectedGraph{
+            graph: Graph::new(),
+        }
+    }
+    pub fn new_large() -> Self {
+        return UndirectedGraph{
+            graph: Graph::new_large(),
+        }
+    }
+    pub fn with_reserve(reserve: usize) -> Self {
+        return UndirectedGraph{
+            graph: Graph::with_reserve(reserve),
+        }
+    }
+}
+
+impl <VertexType, EdgeStorageType> UndirectedGraph<VertexType, EdgeStorageType>{
+    pub fn with_storage(edges: EdgeStorageType) -> Self {
+        return UndirectedGraph{
+            graph: Graph::with_storage(edges),
+        }
+    }
+}
+
+impl<VertexType, EdgeStorageType> UndirectedGraph<VertexType, EdgeStorageType>
+where EdgeStorageType: EdgeStoreMut + EdgeOperator + TraverseMarker + WeightedEdgeOperator {
+    pub fn connect(&mut self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>) {
+        self.connect_weighted(a, b, Default::default());
+    }
+
+    pub fn connect_weighted(&mut self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>, weight: WeightOf<EdgeStorageType>) {
+        if let Err(error) = self.try_connect_weighted(a, b, weight) {
+            panic!("Failed to connect {} and {}: {}", a, b, error);
+        }
+    }
+
+    pub fn try_connect(&mut self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        return self.try_connect_weighted(a, b, Default::default());
+    }
+
+    /// Adds the edge in both directions. If the second direction fails the first one is rolled back.
+    /// Fails with `EdgeExists` if `a` and `b` are already connected.
+    pub fn try_connect_weighted(&mut self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>, weight: WeightOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        self.graph.check_handle(a)?;
+        self.graph.check_handle(b)?;
+        if self.is_connected(a, b) {
+            return Err(Error::EdgeExists { src: a, target: b });
+        }
+        self.graph.edges.try_connect_weighted(a, b, weight)?;
+        if a == b {
+            return Ok(());
+        }
+        if let Err(error) = self.graph.edges.try_connect_weighted(b, a, weight) {
+            self.graph.edges.disconnect(a, b);
+            return Err(error);
+        }
+        return Ok(());
+    }
+
+    pub fn disconnect(&mut self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>) {
+        self.graph.edges.disconnect(a, b);
+        if a != b {
+            self.graph.edges.disconnect(b, a);
+        }
+    }
+
+    pub fn try_disconnect(&mut self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        self.graph.check_handle(a)?;
+        self.graph.check_handle(b)?;
+        self.graph.edges.try_disconnect(a, b)?;
+        if a != b {
+            self.graph.edges.disconnect(b, a);
+        }
+        return Ok(());
+    }
+
+    /// Changes the weight of the edge between `a` and `b` in both directions.
+    pub fn set_weight(&mut self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>, weight: WeightOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        self.graph.check_handle(a)?;
+        self.graph.check_handle(b)?;
+        for (src, target) in [(a, b), (b, a)] {
+            if !self.is_connected(src, target) {
+                return Err(Error::EdgeNotFound { src, target });
+            }
+        }
+        self.graph.edges.try_set_weight(a, b, weight)?;
+        if a != b {
+            self.graph.edges.try_set_weight(b, a, weight)?;
+        }
+        return Ok(());
+    }
+
+    /// Weight of the edge between `a` and `b`, `None` if there is none or either vertex does not exist.
+    pub fn weight(&self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>) -> Option<WeightOf<EdgeStorageType>> {
+        self.graph.check_handle(a).ok()?;
+        self.graph.check_handle(b).ok()?;
+        return self.graph.edges.edges(a).iter().find(|edge| edge.vh() == b).map(|edge| edge.wgt());
+    }
+
+    /// Whether `a` and `b` are connected. `false` if either vertex does not exist.
+    pub fn is_connected(&self, a: VHandleOf<EdgeStorageType>, b: VHandleOf<EdgeStorageType>) -> bool {
+        return self.weight(a, b).is_some();
+    }
+
+    pub fn neighbors(&self, vertex: VHandleOf<EdgeStorageType>) -> Result<impl Iterator<Item = VHandleOf<EdgeStorageType>> + '_, Error<VHandleOf<EdgeStorageType>>> {
+        return Ok(self.graph.try_edges(vertex)?.iter().map(|edge| edge.vh()));
+    }
+
+    /// Number of edge endpoints at `vertex`. Self-loops count twice.
+    pub fn degree(&self, vertex: VHandleOf<EdgeStorageType>) -> Result<usize, Error<VHandleOf<EdgeStorageType>>> {
+        let edges = self.graph.try_edges(vertex)?;
+        let self_loops = edges.iter().filter(|edge| edge.vh() == vertex).count();
+        return Ok(edges.len() + self_loops);
+    }
+
+    /// Number of undirected edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        let mut degree_sum = 0;
+        for vertex in 0..self.graph.vertices.len() {
+            let vertex = VHandleOf::<EdgeStorageType>::from_index(vertex);
+            if let Ok(degree) = self.degree(vertex) {
+                degree_sum += degree;
+            }
+        }
+        return degree_sum / 2;
+    }
+
+    pub fn create_and_connect(&mut self, src_vertex: VHandleOf<EdgeStorageType>, val: VertexType, edge_count: usize) -> VHandleOf<EdgeStorageType> {
+        return self.create_and_connect_weighted(src_vertex, val, Default::default(), edge_count);
+    }
+
+    pub fn create_and_connect_leaf(&mut self, src_vertex: VHandleOf<EdgeStorageType>, val: VertexType) -> VHandleOf<EdgeStorageType> {
+        return self.create_and_connect(src_vertex, val, 1);
+    }
+
+    pub fn create_and_connect_weighted(&mut self, src_vertex: VHandleOf<EdgeStorageType>, val: VertexType, weight: WeightOf<EdgeStorageType>, edge_count: usize) -> VHandleOf<EdgeStorageType> {
+        let new_vertex = self.graph.create(val, edge_count);
+        self.connect_weighted(src_vertex, new_vertex, weight);
+        return new_vertex;
+    }
+}
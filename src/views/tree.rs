@@ -1,17 +1,15 @@
-use crate::edge_storage::{EdgeStorage};
 use crate::graph::{Vertices};
-use crate::handles::{NONE, Slot, vh, vh_pack};
-use crate::handles::types::{PackedEdge, VHandle};
-use crate::traits::{EdgeOperator, EdgeStore, EdgeStoreMut, TraverseMarker};
+use crate::handles::{EdgePacking, HandleIndex};
+use crate::traits::{EdgeOf, EdgeOperator, EdgeStoreMut, TraverseMarker, VHandleOf};
 
 pub struct TreeView<'a, VertexType, EdgeStorageType> {
     pub nodes: &'a mut EdgeStorageType,
     pub values: &'a mut Vertices<VertexType>,
 }
 
-const TREE_HEADER_ELEMENTS: Slot = 2;
-const ROOT_OFFSET: Slot = 0;
-const PARENT_OFFSET: Slot = 1;
+const TREE_HEADER_ELEMENTS: usize = 2;
+const ROOT_OFFSET: usize = 0;
+const PARENT_OFFSET: usize = 1;
 
 
 
@@ -25,45 +23,45 @@ where EdgeStorageType: EdgeStoreMut + EdgeOperator + TraverseMarker {
         }
     }
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn get_children(&self, parent: VHandle) -> &[PackedEdge] {
+    pub fn get_children(&self, parent: VHandleOf<EdgeStorageType>) -> &[EdgeOf<EdgeStorageType>] {
         return self.nodes.edges_offset(parent, TREE_HEADER_ELEMENTS);
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn add_child(&mut self, parent: VHandle, child: VHandle){
+    pub fn add_child(&mut self, parent: VHandleOf<EdgeStorageType>, child: VHandleOf<EdgeStorageType>){
         self.nodes.connect(parent, child);
-        self.nodes.set(child, vh_pack(parent), PARENT_OFFSET);
-        self.nodes.set(child, vh_pack(self.get_root(parent)), ROOT_OFFSET);
+        self.nodes.set(child, EdgeOf::<EdgeStorageType>::vh_pack(parent), PARENT_OFFSET);
+        self.nodes.set(child, EdgeOf::<EdgeStorageType>::vh_pack(self.get_root(parent)), ROOT_OFFSET);
     }
 
-    fn create_vertex(&mut self, val: VertexType) -> VHandle {
+    fn create_vertex(&mut self, val: VertexType) -> VHandleOf<EdgeStorageType> {
         self.values.push(val);
         self.nodes.extend_edge_storage(0);
         let vertex = self.values.len() -1;
-        return vertex as VHandle;
+        return VHandleOf::<EdgeStorageType>::from_index(vertex);
     }
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn get_root(&self, vertex: VHandle) -> VHandle {
-        return vh(self.nodes.get(vertex, 0));
+    pub fn get_root(&self, vertex: VHandleOf<EdgeStorageType>) -> VHandleOf<EdgeStorageType> {
+        return self.nodes.get(vertex, ROOT_OFFSET).vh();
     }
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn get_parent(&self, vertex: VHandle) -> VHandle {
-        return vh(self.nodes.get(vertex, 1));
+    pub fn get_parent(&self, vertex: VHandleOf<EdgeStorageType>) -> VHandleOf<EdgeStorageType> {
+        return self.nodes.get(vertex, PARENT_OFFSET).vh();
     }
 
-    pub fn create_node(&mut self, val: VertexType) -> VHandle {
+    pub fn create_node(&mut self, val: VertexType) -> VHandleOf<EdgeStorageType> {
         let vertex = self.create_vertex(val);
 
         self.nodes.connect(vertex, vertex); // root
-        self.nodes.connect(vertex, NONE); // parent
+        self.nodes.connect(vertex, EdgeOf::<EdgeStorageType>::NONE); // parent
 
         return vertex;
     }
 
     #[cfg_attr(not(debug_assertions), inline(always))]
-    pub fn create_child(&mut self, parent: VHandle, val: VertexType) -> VHandle {
+    pub fn create_child(&mut self, parent: VHandleOf<EdgeStorageType>, val: VertexType) -> VHandleOf<EdgeStorageType> {
         let child = self.create_node(val);
         self.add_child(parent, child);
         return child;
     }
-}
\ No newline at end of file
+}
@@ -1,8 +1,6 @@
 use crate::edge_storage::EdgeStorage;
-use crate::graph::{Graph, Vertices};
-use crate::handles::Slot;
-use crate::handles::types::{VHandle, Weight};
-use crate::traits::{EdgeOperator, EdgeStoreMut, TraverseMarker, WeightedEdgeOperator};
+use crate::graph::{Error, Graph};
+use crate::traits::{EdgeOperator, EdgeStoreMut, TraverseMarker, VHandleOf, WeightedEdgeOperator, WeightOf};
 
 pub struct WeightedGraph<VertexType, EdgeStorageType> {
     pub graph: Graph<VertexType, EdgeStorageType>,
@@ -19,22 +17,41 @@ impl <VertexType> WeightedGraph<VertexType, EdgeStorage>{
             graph: Graph::new_large(),
         }
     }
-    pub fn with_reserve(reserve: Slot) -> Self {
+    pub fn with_reserve(reserve: usize) -> Self {
         return WeightedGraph{
             graph: Graph::with_reserve(reserve),
         }
     }
 }
+
+impl <VertexType, EdgeStorageType> WeightedGraph<VertexType, EdgeStorageType>{
+    pub fn with_storage(edges: EdgeStorageType) -> Self {
+        return WeightedGraph{
+            graph: Graph::with_storage(edges),
+        }
+    }
+}
+
 impl<VertexType, EdgeStorageType> WeightedGraph<VertexType, EdgeStorageType>
 where EdgeStorageType: EdgeStoreMut + EdgeOperator + TraverseMarker + WeightedEdgeOperator {
-    pub fn create_and_connect_weighted(&mut self, src_vertex: VHandle, val: VertexType, weight: Weight, edge_count: Slot) -> VHandle {
+    pub fn create_and_connect_weighted(&mut self, src_vertex: VHandleOf<EdgeStorageType>, val: VertexType, weight: WeightOf<EdgeStorageType>, edge_count: usize) -> VHandleOf<EdgeStorageType> {
         let new_vertex = self.graph.create(val, edge_count);
         self.graph.edges.connect_weighted(src_vertex, new_vertex, weight);
         return new_vertex;
     }
 
-    pub fn create_and_connect_leaf_weighted(&mut self, src_vertex: VHandle, val: VertexType, weight: Weight) -> VHandle {
+    pub fn try_connect_weighted(&mut self, src: VHandleOf<EdgeStorageType>, target: VHandleOf<EdgeStorageType>, weight: WeightOf<EdgeStorageType>) -> Result<(), Error<VHandleOf<EdgeStorageType>>> {
+        if !self.graph.is_alive(src) {
+            return Err(Error::InvalidHandle(src));
+        }
+        if !self.graph.is_alive(target) {
+            return Err(Error::InvalidHandle(target));
+        }
+        return self.graph.edges.try_connect_weighted(src, target, weight);
+    }
+
+    pub fn create_and_connect_leaf_weighted(&mut self, src_vertex: VHandleOf<EdgeStorageType>, val: VertexType, weight: WeightOf<EdgeStorageType>) -> VHandleOf<EdgeStorageType> {
         return self.create_and_connect_weighted(src_vertex, val, weight, 0);
     }
 
-}
\ No newline at end of file
+}